@@ -0,0 +1,347 @@
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Beta, Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+use crate::ties::Ties;
+
+/// Reward distribution of every arm
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arms {
+    /// $\mathcal{N}(q_*(a), 1)$
+    Gaussian,
+    /// $1$ with probability $q_*(a)$, else $0$
+    Bernoulli,
+}
+
+/// A family of $k$-armed bandit problems to draw runs from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Testbed {
+    pub k: usize,
+    pub arms: Arms,
+    /// Standard deviation of the random walk every $q_*(a)$ takes after each step; $0$ if stationary
+    pub drift: f64,
+    /// Added to every Gaussian $q_*(a)$ when drawn
+    pub offset: f64,
+}
+
+impl Testbed {
+    /// Section 2.3: $q_*(a) \sim \mathcal{N}(0, 1)$
+    pub fn stationary(k: usize) -> Self {
+        Self {
+            k,
+            arms: Arms::Gaussian,
+            drift: 0.0,
+            offset: 0.0,
+        }
+    }
+
+    /// Exercise 2.5: all $q_*(a)$ start equal and take independent random walks
+    pub fn nonstationary(k: usize, drift: f64) -> Self {
+        Self {
+            k,
+            arms: Arms::Gaussian,
+            drift,
+            offset: 0.0,
+        }
+    }
+
+    /// $q_*(a) \sim U(0, 1)$
+    pub fn bernoulli(k: usize) -> Self {
+        Self {
+            k,
+            arms: Arms::Bernoulli,
+            drift: 0.0,
+            offset: 0.0,
+        }
+    }
+
+    /// Figure 2.5 shifts the stationary testbed by $+4$
+    pub fn with_offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn sample(&self, rng: &mut dyn RngCore) -> Bandit {
+        let means = (0..self.k)
+            .map(|_| match self.arms {
+                Arms::Gaussian if 0.0 < self.drift => self.offset,
+                Arms::Gaussian => {
+                    let mean: f64 = StandardNormal.sample(rng);
+                    self.offset + mean
+                }
+                Arms::Bernoulli => rng.gen_range(0.0..1.0),
+            })
+            .collect();
+        Bandit {
+            means,
+            arms: self.arms,
+            drift: self.drift,
+        }
+    }
+}
+
+/// One problem of a [`Testbed`]
+#[derive(Debug, Clone)]
+pub struct Bandit {
+    /// $q_*(a)$
+    pub means: Vec<f64>,
+    arms: Arms,
+    drift: f64,
+}
+
+impl Bandit {
+    pub fn pull(&self, a: usize, rng: &mut dyn RngCore) -> f64 {
+        match self.arms {
+            Arms::Gaussian => {
+                let noise: f64 = StandardNormal.sample(rng);
+                self.means[a] + noise
+            }
+            Arms::Bernoulli => {
+                if rng.gen_bool(self.means[a].clamp(0.0, 1.0)) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// $\operatorname{argmax}_a q_*(a)$
+    pub fn optimal(&self) -> usize {
+        (0..self.means.len())
+            .max_by(|a, b| self.means[*a].total_cmp(&self.means[*b]))
+            .unwrap()
+    }
+
+    /// Move every $q_*(a)$ one step of its random walk
+    pub fn walk(&mut self, rng: &mut dyn RngCore) {
+        if self.drift == 0.0 {
+            return;
+        }
+        for mean in self.means.iter_mut() {
+            let step: f64 = StandardNormal.sample(rng);
+            *mean += self.drift * step;
+        }
+    }
+}
+
+/// How to pick arms, and learn from their rewards
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Agent {
+    /// $\epsilon$-greedy on sample averages
+    EpsilonGreedy { epsilon: f64 },
+    /// $\epsilon$-greedy on estimates with a constant step size $\alpha$
+    ConstantStep { epsilon: f64, alpha: f64 },
+    /// Like `ConstantStep`, with every estimate starting at `initial_value`
+    Optimistic {
+        initial_value: f64,
+        epsilon: f64,
+        alpha: f64,
+    },
+    /// UCB1: $\operatorname{argmax}_a Q_t(a) + c \sqrt{\ln t / N_t(a)}$, trying every arm once first
+    Ucb { c: f64 },
+    /// Softmax over preferences $H_t(a)$ by stochastic gradient ascent with step size $\alpha$
+    Gradient {
+        alpha: f64,
+        /// Subtract the average reward $\bar{R}_t$ from rewards, or else zero
+        baseline: bool,
+    },
+    /// Posterior $\mathcal{N}(\frac{\sum R}{1 + N}, \frac{1}{1 + N})$ from a $\mathcal{N}(0, 1)$ prior
+    /// and rewards of unit variance
+    GaussianThompson,
+    /// Posterior $\mathrm{Beta}(1 + \sum R, 1 + N - \sum R)$ for rewards in $\{0, 1\}$, so only on
+    /// [`Arms::Bernoulli`]
+    BernoulliThompson,
+}
+
+/// What an [`Agent`] has learned on one run
+#[derive(Debug, Clone)]
+pub struct AgentState {
+    /// $Q_t(a)$
+    pub value: Vec<f64>,
+    /// $N_t(a)$
+    pub visits: Vec<usize>,
+    /// Sum of the rewards of each arm
+    pub rewards: Vec<f64>,
+    /// $H_t(a)$
+    pub preferences: Vec<f64>,
+    /// $\bar{R}_t$
+    pub average_reward: f64,
+    pub steps: usize,
+}
+
+impl Agent {
+    /// Whether the agent can learn from the rewards of `arms`
+    pub fn check(&self, arms: Arms) -> Result<(), String> {
+        match (self, arms) {
+            (Agent::BernoulliThompson, Arms::Gaussian) => {
+                Err("Bernoulli Thompson sampling needs rewards in {0, 1}, not Gaussian arms".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn initial_state(&self, k: usize) -> AgentState {
+        let initial_value = match self {
+            Agent::Optimistic { initial_value, .. } => *initial_value,
+            _ => 0.0,
+        };
+        AgentState {
+            value: vec![initial_value; k],
+            visits: vec![0; k],
+            rewards: vec![0.0; k],
+            preferences: vec![0.0; k],
+            average_reward: 0.0,
+            steps: 0,
+        }
+    }
+
+    pub fn select(&self, state: &AgentState, rng: &mut dyn RngCore) -> usize {
+        let k = state.value.len();
+        let greedy = |values: Vec<f64>, rng: &mut dyn RngCore| -> usize {
+            let (_, arms) = Ties::default().max(values.into_iter().enumerate());
+            *arms.choose(rng).unwrap()
+        };
+        match *self {
+            Agent::EpsilonGreedy { epsilon }
+            | Agent::ConstantStep { epsilon, .. }
+            | Agent::Optimistic { epsilon, .. } => {
+                if rng.gen_bool(epsilon) {
+                    rng.gen_range(0..k)
+                } else {
+                    greedy(state.value.clone(), rng)
+                }
+            }
+            Agent::Ucb { c } => {
+                if let Some(a) = state.visits.iter().position(|n| *n == 0) {
+                    return a;
+                }
+                let t = state.steps as f64;
+                let bounds = (0..k)
+                    .map(|a| state.value[a] + c * (t.ln() / state.visits[a] as f64).sqrt())
+                    .collect();
+                greedy(bounds, rng)
+            }
+            Agent::Gradient { .. } => {
+                let probabilities = softmax(&state.preferences);
+                *(0..k)
+                    .collect::<Vec<_>>()
+                    .choose_weighted(rng, |a| probabilities[*a])
+                    .unwrap()
+            }
+            Agent::GaussianThompson => {
+                let samples = (0..k)
+                    .map(|a| {
+                        let precision = 1.0 + state.visits[a] as f64;
+                        let z: f64 = StandardNormal.sample(rng);
+                        state.rewards[a] / precision + z / precision.sqrt()
+                    })
+                    .collect();
+                greedy(samples, rng)
+            }
+            Agent::BernoulliThompson => {
+                let samples = (0..k)
+                    .map(|a| {
+                        let successes = state.rewards[a];
+                        let failures = state.visits[a] as f64 - successes;
+                        Beta::new(1.0 + successes, 1.0 + failures)
+                            .unwrap()
+                            .sample(rng)
+                    })
+                    .collect();
+                greedy(samples, rng)
+            }
+        }
+    }
+
+    pub fn update(&self, state: &mut AgentState, a: usize, r: f64) {
+        state.steps += 1;
+        state.visits[a] += 1;
+        state.rewards[a] += r;
+        state.average_reward += (r - state.average_reward) / state.steps as f64;
+        match *self {
+            Agent::EpsilonGreedy { .. } | Agent::Ucb { .. } => {
+                state.value[a] += (r - state.value[a]) / state.visits[a] as f64;
+            }
+            Agent::ConstantStep { alpha, .. } | Agent::Optimistic { alpha, .. } => {
+                state.value[a] += alpha * (r - state.value[a]);
+            }
+            Agent::Gradient {
+                alpha,
+                baseline: with_baseline,
+            } => {
+                // the average already counts `r`, so that $\bar{R}_1 = R_1$ as in Section 2.8
+                let baseline = if with_baseline {
+                    state.average_reward
+                } else {
+                    0.0
+                };
+                let probabilities = softmax(&state.preferences);
+                for (b, h) in state.preferences.iter_mut().enumerate() {
+                    let indicator = if a == b { 1.0 } else { 0.0 };
+                    *h += alpha * (r - baseline) * (indicator - probabilities[b]);
+                }
+            }
+            Agent::GaussianThompson | Agent::BernoulliThompson => {
+                state.value[a] = state.rewards[a] / state.visits[a] as f64;
+            }
+        }
+    }
+}
+
+fn softmax(preferences: &[f64]) -> Vec<f64> {
+    let max = preferences.iter().copied().fold(f64::MIN, f64::max);
+    let exps: Vec<f64> = preferences.iter().map(|h| (h - max).exp()).collect();
+    let sum: f64 = exps.iter().sum();
+    exps.iter().map(|e| e / sum).collect()
+}
+
+/// Per-step averages over runs, as in Figure 2.2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Curves {
+    pub average_reward: Vec<f64>,
+    /// Fraction of runs that picked an optimal arm at each step
+    pub optimal_action: Vec<f64>,
+}
+
+/// Run `agent` for `steps` steps on each of `runs` bandits drawn from `testbed`
+///
+/// Run $i$ draws its bandit and everything after from an RNG of its own seeded by `seed + i`.
+/// Fails if `agent` cannot learn from the arms of `testbed`.
+pub fn run(
+    testbed: &Testbed,
+    agent: &Agent,
+    steps: usize,
+    runs: usize,
+    seed: u64,
+) -> Result<Curves, String> {
+    agent.check(testbed.arms)?;
+    let mut average_reward = vec![0.0; steps];
+    let mut optimal_action = vec![0.0; steps];
+    for run in 0..runs {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed + run as u64);
+        let mut bandit = testbed.sample(&mut rng);
+        let mut state = agent.initial_state(testbed.k);
+        for t in 0..steps {
+            let a = agent.select(&state, &mut rng);
+            let r = bandit.pull(a, &mut rng);
+            agent.update(&mut state, a, r);
+            average_reward[t] += r;
+            if bandit.means[a] == bandit.means[bandit.optimal()] {
+                optimal_action[t] += 1.0;
+            }
+            bandit.walk(&mut rng);
+        }
+    }
+    for t in 0..steps {
+        average_reward[t] /= runs as f64;
+        optimal_action[t] /= runs as f64;
+    }
+    Ok(Curves {
+        average_reward,
+        optimal_action,
+    })
+}
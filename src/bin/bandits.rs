@@ -0,0 +1,183 @@
+use reinforcement_learning::{
+    bandits::{run, Agent, Testbed},
+    plot::{self, Chart, Figure, Series},
+    render::table,
+};
+
+const K: usize = 10;
+const STEPS: usize = 1000;
+const RUNS: usize = 2000;
+const SEED: u64 = 0;
+/// Figure 2.5
+const GRADIENT_OFFSET: f64 = 4.0;
+/// Exercise 2.5
+const DRIFT: f64 = 0.01;
+const NONSTATIONARY_STEPS: usize = 10_000;
+const NONSTATIONARY_RUNS: usize = 200;
+const AVERAGE_REWARD_PLOT_FILE: &str = "bandits.average_reward.html";
+const OPTIMAL_ACTION_PLOT_FILE: &str = "bandits.optimal_action.html";
+
+fn main() {
+    {
+        // Figure 2.2
+        let mut average_reward = Figure::new("10-armed testbed", "Steps", "Average reward");
+        let mut optimal_action = Figure::new("10-armed testbed", "Steps", "% Optimal action");
+        for epsilon in [0.0, 0.01, 0.1] {
+            let curves = run(
+                &Testbed::stationary(K),
+                &Agent::EpsilonGreedy { epsilon },
+                STEPS,
+                RUNS,
+                SEED,
+            )
+            .unwrap();
+            let name = format!("ε={}", epsilon);
+            let x: Vec<f64> = (1..=STEPS).map(|t| t as f64).collect();
+            average_reward = average_reward.with_series(Series {
+                name: name.clone(),
+                x: x.clone(),
+                y: curves.average_reward,
+                mode: plot::Mode::Lines,
+                band: None,
+            });
+            optimal_action = optimal_action.with_series(Series {
+                name,
+                x,
+                y: curves.optimal_action.iter().map(|p| 100.0 * p).collect(),
+                mode: plot::Mode::Lines,
+                band: None,
+            });
+        }
+        average_reward.save(AVERAGE_REWARD_PLOT_FILE).unwrap();
+        optimal_action.save(OPTIMAL_ACTION_PLOT_FILE).unwrap();
+        println!("{}, {}", AVERAGE_REWARD_PLOT_FILE, OPTIMAL_ACTION_PLOT_FILE);
+    }
+    println!();
+
+    // Figures 2.3 to 2.5, Thompson sampling, and Exercise 2.5
+    let comparisons = [
+        (
+            "ε=0.1",
+            Testbed::stationary(K),
+            Agent::EpsilonGreedy { epsilon: 0.1 },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "optimistic Q1=5, ε=0",
+            Testbed::stationary(K),
+            Agent::Optimistic {
+                initial_value: 5.0,
+                epsilon: 0.0,
+                alpha: 0.1,
+            },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "realistic Q1=0, ε=0.1",
+            Testbed::stationary(K),
+            Agent::ConstantStep {
+                epsilon: 0.1,
+                alpha: 0.1,
+            },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "UCB c=2",
+            Testbed::stationary(K),
+            Agent::Ucb { c: 2.0 },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "gradient α=0.1, baseline",
+            Testbed::stationary(K).with_offset(GRADIENT_OFFSET),
+            Agent::Gradient {
+                alpha: 0.1,
+                baseline: true,
+            },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "gradient α=0.1, no baseline",
+            Testbed::stationary(K).with_offset(GRADIENT_OFFSET),
+            Agent::Gradient {
+                alpha: 0.1,
+                baseline: false,
+            },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "Gaussian Thompson",
+            Testbed::stationary(K),
+            Agent::GaussianThompson,
+            STEPS,
+            RUNS,
+        ),
+        (
+            "Bernoulli ε=0.1",
+            Testbed::bernoulli(K),
+            Agent::EpsilonGreedy { epsilon: 0.1 },
+            STEPS,
+            RUNS,
+        ),
+        (
+            "Bernoulli Thompson",
+            Testbed::bernoulli(K),
+            Agent::BernoulliThompson,
+            STEPS,
+            RUNS,
+        ),
+        (
+            "nonstationary sample averages",
+            Testbed::nonstationary(K, DRIFT),
+            Agent::EpsilonGreedy { epsilon: 0.1 },
+            NONSTATIONARY_STEPS,
+            NONSTATIONARY_RUNS,
+        ),
+        (
+            "nonstationary α=0.1",
+            Testbed::nonstationary(K, DRIFT),
+            Agent::ConstantStep {
+                epsilon: 0.1,
+                alpha: 0.1,
+            },
+            NONSTATIONARY_STEPS,
+            NONSTATIONARY_RUNS,
+        ),
+    ];
+    let mut names = vec![];
+    let mut cells = vec![];
+    for (name, testbed, agent, steps, runs) in comparisons {
+        let curves = run(&testbed, &agent, steps, runs, SEED).unwrap();
+        let last = steps / 10;
+        let mean = |curve: &[f64]| curve.iter().sum::<f64>() / curve.len() as f64;
+        names.push(name.to_string());
+        cells.push(vec![
+            steps.to_string(),
+            format!("{:.3}", mean(&curves.average_reward)),
+            format!("{:.3}", mean(&curves.average_reward[steps - last..])),
+            format!(
+                "{:.1}",
+                100.0 * mean(&curves.optimal_action[steps - last..])
+            ),
+        ]);
+    }
+    print!(
+        "{}",
+        table(
+            &[
+                "steps".to_string(),
+                "reward".to_string(),
+                "last 10% reward".to_string(),
+                "last 10% optimal".to_string(),
+            ],
+            &names,
+            &cells,
+        )
+    );
+}
@@ -0,0 +1,55 @@
+use reinforcement_learning::{
+    exploration::Exploration,
+    games::blackjack::Blackjack,
+    history::History,
+    monte_carlo::{MonteCarlo, MonteCarloState},
+    plot::{self, Chart},
+    q_learning::{QLearning, QLearningState},
+    schedule::Schedule,
+};
+
+const EPSILON: f64 = 0.1;
+const ALPHA: f64 = 0.1;
+const NUM_EPISODES: usize = 10_000;
+const NUM_SEEDS: u64 = 10;
+const SMOOTHING_WINDOW: usize = 500;
+const LEARNING_CURVES_FILE: &str = "blackjack.learning_curves.html";
+
+fn main() {
+    let exploration = Exploration::EpsilonGreedy {
+        epsilon: Schedule::Constant(EPSILON),
+    };
+    let monte_carlo = MonteCarlo::new(Box::new(Blackjack));
+    let q_learning = QLearning::new(Box::new(Blackjack));
+
+    let mut monte_carlo_runs: Vec<History> = vec![];
+    let mut q_learning_runs: Vec<History> = vec![];
+    for seed in 0..NUM_SEEDS {
+        let mut state = MonteCarloState::new(seed);
+        monte_carlo_runs.push(monte_carlo.policy_evaluation(
+            &mut state,
+            &exploration,
+            NUM_EPISODES,
+            None,
+        ));
+        let mut state = QLearningState::new(seed);
+        q_learning_runs.push(q_learning.value_evaluation(
+            &mut state,
+            &exploration,
+            Schedule::Constant(ALPHA),
+            NUM_EPISODES,
+            None,
+        ));
+    }
+
+    plot::learning_curves(
+        &[
+            ("Monte Carlo", &monte_carlo_runs),
+            ("Q-learning", &q_learning_runs),
+        ],
+        SMOOTHING_WINDOW,
+    )
+    .save(LEARNING_CURVES_FILE)
+    .unwrap();
+    println!("{}", LEARNING_CURVES_FILE);
+}
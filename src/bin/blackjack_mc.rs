@@ -1,31 +1,77 @@
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::Write,
-    path,
-};
+use std::path::Path;
 
-use reinforcement_learning::{games::blackjack::Blackjack, monte_carlo::MonteCarlo};
+use reinforcement_learning::{
+    evaluation::evaluate,
+    exploration::Exploration,
+    games::blackjack::{self, Blackjack},
+    monte_carlo::{MonteCarlo, MonteCarloState},
+    plot::{self, Chart},
+    render::{max_over_actions, Render},
+    schedule::Schedule,
+    serialization::{load_checkpoint, save_checkpoint, save_policy, CheckpointHeader, Format},
+};
 
 const EPSILON: f64 = 0.1;
 const NUM_EPISODES: usize = 10_000;
-const ACTION_OUTPUT_FILE: &str = "blackjack.action.txt";
+const SEED: u64 = 0;
+const CHECKPOINT_EVERY: usize = 1_000;
+/// Set this environment variable to continue from the checkpoint instead of starting over
+const RESUME_VAR: &str = "RESUME";
+const CHECKPOINT_FILE: &str = "blackjack.checkpoint.bin";
+const EVALUATION_EPISODES: usize = 100_000;
+const EVALUATION_SEED: u64 = 0;
+const ACTION_OUTPUT_FILE: &str = "blackjack.action.json";
+const VALUE_PLOT_FILE: &str = "blackjack.value.html";
+const POLICY_PLOT_FILE: &str = "blackjack.policy.html";
 
 fn main() {
     let task = Blackjack;
     let monte_carlo = MonteCarlo::new(Box::new(task));
-    let mut q = HashMap::new();
-    let mut c = HashMap::new();
-    let mut pi = HashMap::new();
-    monte_carlo.policy_evaluation(&mut q, &mut c, &mut pi, EPSILON, NUM_EPISODES);
-
-    println!("(s, a)");
-    if let Err(e) = fs::remove_file(path::Path::new(ACTION_OUTPUT_FILE)) {
-        if e.kind() != std::io::ErrorKind::NotFound {
-            panic!("{}", e);
-        }
+    let header = CheckpointHeader::new(
+        "Blackjack MonteCarlo",
+        &serde_json::json!({
+            "seed": SEED,
+            "epsilon": EPSILON,
+        }),
+    );
+    // resume from the last checkpoint only if asked to, as the constants above may have changed
+    let resume = std::env::var_os(RESUME_VAR).is_some();
+    let mut state: MonteCarloState<_, _> = if resume && Path::new(CHECKPOINT_FILE).exists() {
+        load_checkpoint(CHECKPOINT_FILE, Format::Binary, &header)
+            .unwrap_or_else(|e| panic!("cannot resume {}: {}", CHECKPOINT_FILE, e))
+    } else {
+        MonteCarloState::new(SEED)
+    };
+    let exploration = Exploration::EpsilonGreedy {
+        epsilon: Schedule::Constant(EPSILON),
+    };
+    while state.episode < NUM_EPISODES {
+        let num_episodes = usize::min(CHECKPOINT_EVERY, NUM_EPISODES - state.episode);
+        monte_carlo.policy_evaluation(&mut state, &exploration, num_episodes, None);
+        save_checkpoint(CHECKPOINT_FILE, Format::Binary, &header, &state).unwrap();
     }
-    let mut file = File::create(ACTION_OUTPUT_FILE).unwrap();
-    // println!("{:#?}", pi);
-    writeln!(file, "{:#?}", pi).unwrap();
+    let pi = state.policy;
+    println!(
+        "{:#?}",
+        evaluate(
+            monte_carlo.task(),
+            &pi,
+            EVALUATION_EPISODES,
+            None,
+            EVALUATION_SEED
+        )
+    );
+
+    print!(
+        "{}",
+        Blackjack.render_values(&max_over_actions(&state.value))
+    );
+    print!("{}", Blackjack.render_policy(&pi));
+
+    save_policy(ACTION_OUTPUT_FILE, Format::Json, &pi).unwrap();
+    let [a, b] = blackjack::value_surfaces(&state.value);
+    plot::save_panels(VALUE_PLOT_FILE, &[&a as &dyn Chart, &b], 2).unwrap();
+    let [a, b] = blackjack::policy_grids(&pi);
+    plot::save_panels(POLICY_PLOT_FILE, &[&a as &dyn Chart, &b], 2).unwrap();
+    println!("{}, {}", VALUE_PLOT_FILE, POLICY_PLOT_FILE);
 }
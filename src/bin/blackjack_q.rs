@@ -1,30 +1,95 @@
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::Write,
-    path,
-};
+use std::{collections::HashMap, path::Path};
 
-use reinforcement_learning::{games::blackjack::Blackjack, q_learning::QLearning};
+use reinforcement_learning::{
+    evaluation::evaluate,
+    exploration::Exploration,
+    games::blackjack::{self, Blackjack},
+    plot::{self, Chart},
+    policy::Greedy,
+    q_learning::{QLearning, QLearningState},
+    render::{max_over_actions, Render},
+    schedule::Schedule,
+    serialization::{
+        load_checkpoint, save_action_values, save_checkpoint, CheckpointHeader, Format,
+    },
+    ties::Ties,
+};
 
 const EPSILON: f64 = 0.1;
 const ALPHA: f64 = 0.1;
 const NUM_EPISODES: usize = 10_000;
-const VALUE_OUTPUT_FILE: &str = "blackjack.Q_learning.action_value.txt";
+const SEED: u64 = 0;
+const CHECKPOINT_EVERY: usize = 1_000;
+/// Set this environment variable to continue from the checkpoint instead of starting over
+const RESUME_VAR: &str = "RESUME";
+const CHECKPOINT_FILE: &str = "blackjack.Q_learning.checkpoint.bin";
+const EVALUATION_EPISODES: usize = 100_000;
+const EVALUATION_SEED: u64 = 0;
+const VALUE_OUTPUT_FILE: &str = "blackjack.Q_learning.action_value.json";
+const VALUE_PLOT_FILE: &str = "blackjack.Q_learning.value.html";
+const POLICY_PLOT_FILE: &str = "blackjack.Q_learning.policy.html";
 
 fn main() {
     let task = Blackjack;
     let q_learning = QLearning::new(Box::new(task));
-    let mut q = HashMap::new();
-    q_learning.value_evaluation(&mut q, EPSILON, ALPHA, NUM_EPISODES);
-
-    println!("((s, a), Q(s, a))");
-    if let Err(e) = fs::remove_file(path::Path::new(VALUE_OUTPUT_FILE)) {
-        if e.kind() != std::io::ErrorKind::NotFound {
-            panic!("{}", e);
-        }
+    let header = CheckpointHeader::new(
+        "Blackjack QLearning",
+        &serde_json::json!({
+            "seed": SEED,
+            "epsilon": EPSILON,
+            "alpha": ALPHA,
+        }),
+    );
+    // resume from the last checkpoint only if asked to, as the constants above may have changed
+    let resume = std::env::var_os(RESUME_VAR).is_some();
+    let mut state: QLearningState<_, _> = if resume && Path::new(CHECKPOINT_FILE).exists() {
+        load_checkpoint(CHECKPOINT_FILE, Format::Binary, &header)
+            .unwrap_or_else(|e| panic!("cannot resume {}: {}", CHECKPOINT_FILE, e))
+    } else {
+        QLearningState::new(SEED)
+    };
+    let exploration = Exploration::EpsilonGreedy {
+        epsilon: Schedule::Constant(EPSILON),
+    };
+    while state.episode < NUM_EPISODES {
+        let num_episodes = usize::min(CHECKPOINT_EVERY, NUM_EPISODES - state.episode);
+        q_learning.value_evaluation(
+            &mut state,
+            &exploration,
+            Schedule::Constant(ALPHA),
+            num_episodes,
+            None,
+        );
+        save_checkpoint(CHECKPOINT_FILE, Format::Binary, &header, &state).unwrap();
     }
-    let mut file = File::create(VALUE_OUTPUT_FILE).unwrap();
-    // println!("{:#?}", pi);
-    writeln!(file, "{:#?}", q).unwrap();
+    let q = state.value;
+    {
+        let greedy = Greedy {
+            value: &q,
+            ties: Ties::default(),
+        };
+        println!(
+            "{:#?}",
+            evaluate(
+                q_learning.task(),
+                &greedy,
+                EVALUATION_EPISODES,
+                None,
+                EVALUATION_SEED
+            )
+        );
+    }
+
+    save_action_values(VALUE_OUTPUT_FILE, Format::Json, &q).unwrap();
+    let [a, b] = blackjack::value_surfaces(&q);
+    plot::save_panels(VALUE_PLOT_FILE, &[&a as &dyn Chart, &b], 2).unwrap();
+    let pi: HashMap<_, _> = q
+        .keys()
+        .map(|sa| (sa.state, q_learning.max_value_by_actions(&q, &sa.state).1))
+        .collect();
+    print!("{}", Blackjack.render_values(&max_over_actions(&q)));
+    print!("{}", Blackjack.render_policy(&pi));
+    let [a, b] = blackjack::policy_grids(&pi);
+    plot::save_panels(POLICY_PLOT_FILE, &[&a as &dyn Chart, &b], 2).unwrap();
+    println!("{}, {}", VALUE_PLOT_FILE, POLICY_PLOT_FILE);
 }
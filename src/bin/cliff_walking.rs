@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::{
+    exploration::Exploration,
+    games::cliff_walking::CliffWalking,
+    q_learning::{QLearning, QLearningState},
+    render::{max_over_actions, Render},
+    schedule::Schedule,
+    value_iteration::{Termination, UpdateScheme, ValueIteration},
+};
+
+const EPSILON: f64 = 0.1;
+const ALPHA: f64 = 0.5;
+const NUM_EPISODES: usize = 500;
+const SEED: u64 = 0;
+const LAST_EPISODES: usize = 100;
+
+fn main() {
+    {
+        let value_iteration = ValueIteration::new(Box::new(CliffWalking));
+        let mut v: HashMap<_, _> = CliffWalking.state_space().map(|s| (s, 0.0)).collect();
+        value_iteration.value_iteration(
+            Termination::Residual(0.01),
+            UpdateScheme::InPlace,
+            None,
+            &mut v,
+        );
+        let policy: HashMap<_, _> = CliffWalking
+            .state_space()
+            .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+            .collect();
+        println!("v*");
+        print!("{}", CliffWalking.render_values(&v));
+        println!("π*");
+        print!("{}", CliffWalking.render_policy(&policy));
+    }
+    println!();
+    {
+        // Example 6.6: ε-greedy Q-learning learns the optimal path along the cliff
+        let q_learning = QLearning::new(Box::new(CliffWalking));
+        let mut state = QLearningState::new(SEED);
+        let history = q_learning.value_evaluation(
+            &mut state,
+            &Exploration::EpsilonGreedy {
+                epsilon: Schedule::Constant(EPSILON),
+            },
+            Schedule::Constant(ALPHA),
+            NUM_EPISODES,
+            None,
+        );
+        let returns = history.returns();
+        let last = &returns[returns.len() - LAST_EPISODES..];
+        println!(
+            "Q-learning: mean return of the last {} episodes: {:.1}",
+            LAST_EPISODES,
+            last.iter().sum::<f64>() / LAST_EPISODES as f64
+        );
+        let q = state.value;
+        let policy: HashMap<_, _> = CliffWalking
+            .state_space()
+            .map(|s| (s, q_learning.max_value_by_actions(&q, &s).1))
+            .collect();
+        print!("{}", CliffWalking.render_values(&max_over_actions(&q)));
+        print!("{}", CliffWalking.render_policy(&policy));
+    }
+}
@@ -1,7 +1,14 @@
 use std::collections::HashMap;
 
-use plotly::{common::Mode, Plot, Scatter};
-use reinforcement_learning::{games::gambler::Gambler, value_iteration::ValueIteration};
+use reinforcement_learning::{
+    games::gambler::Gambler,
+    plot::{self, Chart},
+    render::Render,
+    value_iteration::{Termination, UpdateScheme, ValueIteration},
+};
+
+const VALUE_PLOT_FILE: &str = "gambler.value.html";
+const POLICY_PLOT_FILE: &str = "gambler.policy.html";
 
 fn main() {
     let task = Gambler;
@@ -10,7 +17,16 @@ fn main() {
     for s in value_iteration.task().state_space() {
         v.insert(s, 0.0);
     }
-    value_iteration.value_iteration(0.01, &mut v);
+    let report = value_iteration.value_iteration(
+        Termination::Residual(0.01),
+        UpdateScheme::InPlace,
+        None,
+        &mut v,
+    );
+    println!(
+        "sweeps: {}, elapsed: {:?}, suboptimality bound: {:?}",
+        report.sweeps, report.elapsed, report.suboptimality_bound
+    );
     println!("(s, V(s))");
     for s in value_iteration.task().state_space() {
         println!("({}, {})", s, v[&s]);
@@ -21,31 +37,16 @@ fn main() {
         println!("({}, {:?})", s, value_iteration.max_v_a(&v, &s).1);
     }
 
-    {
-        let mut plot = Plot::new();
-        let mut x = vec![];
-        let mut y = vec![];
-        for s in value_iteration.task().state_space() {
-            x.push(s);
-            y.push(v[&s]);
-        }
-        let trace = Scatter::new(x, y).name("V(s)").mode(Mode::Lines);
-        plot.add_trace(trace);
-        plot.show();
-    }
-    {
-        let mut plot = Plot::new();
-        let mut x = vec![];
-        let mut y = vec![];
-        for s in value_iteration.task().state_space() {
-            let a = value_iteration.max_v_a(&v, &s).1;
-            for a in a {
-                x.push(s);
-                y.push(a);
-            }
-        }
-        let trace = Scatter::new(x, y).name("\\pi(s)").mode(Mode::Markers);
-        plot.add_trace(trace);
-        plot.show();
-    }
+    let policy: HashMap<_, _> = value_iteration
+        .task()
+        .state_space()
+        .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+        .collect();
+    print!("{}", Gambler.render_values(&v));
+    print!("{}", Gambler.render_policy(&policy));
+    plot::value_curve(&v).save(VALUE_PLOT_FILE).unwrap();
+    plot::policy_scatter(&policy)
+        .save(POLICY_PLOT_FILE)
+        .unwrap();
+    println!("{}, {}", VALUE_PLOT_FILE, POLICY_PLOT_FILE);
 }
@@ -1,18 +1,31 @@
-use std::collections::HashMap;
-
-use plotly::{common::Mode, Plot, Scatter};
-use reinforcement_learning::{games::gambler::Gambler, monte_carlo::MonteCarlo};
+use reinforcement_learning::{
+    exploration::Exploration,
+    games::gambler::Gambler,
+    monte_carlo::{MonteCarlo, MonteCarloState},
+    plot::{self, Chart},
+    render::Render,
+    schedule::Schedule,
+};
 
 const EPSILON: f64 = 0.1;
 const NUM_EPISODES: usize = 10_000;
+// betting 0 never ends an episode
+const MAX_STEPS: usize = 1_000;
+const SEED: u64 = 0;
+const SMOOTHING_WINDOW: usize = 100;
+const POLICY_PLOT_FILE: &str = "gambler2.policy.html";
+const LEARNING_CURVE_FILE: &str = "gambler2.learning_curve.html";
 
 fn main() {
     let task = Gambler;
-    let monte_carlo = MonteCarlo::new(Box::new(task));
-    let mut q = HashMap::new();
-    let mut c = HashMap::new();
-    let mut pi = HashMap::new();
-    monte_carlo.policy_evaluation(&mut q, &mut c, &mut pi, EPSILON, NUM_EPISODES);
+    let monte_carlo = MonteCarlo::new(Box::new(task)).with_max_steps(MAX_STEPS);
+    let mut state = MonteCarloState::new(SEED);
+    let exploration = Exploration::EpsilonGreedy {
+        epsilon: Schedule::Constant(EPSILON),
+    };
+    let history = monte_carlo.policy_evaluation(&mut state, &exploration, NUM_EPISODES, None);
+    println!("truncated episodes: {}", history.num_truncated());
+    let pi = state.policy;
 
     let task = Gambler;
     println!("(s, a)");
@@ -20,20 +33,10 @@ fn main() {
         println!("({}, {:?})", s, pi.get(&s));
     }
 
-    {
-        let mut plot = Plot::new();
-        let mut x = vec![];
-        let mut y = vec![];
-        for s in task.state_space() {
-            if let Some(a) = pi.get(&s) {
-                for a in a {
-                    x.push(s);
-                    y.push(*a);
-                }
-            }
-        }
-        let trace = Scatter::new(x, y).name("\\pi(s)").mode(Mode::Markers);
-        plot.add_trace(trace);
-        plot.show();
-    }
+    print!("{}", task.render_policy(&pi));
+    plot::policy_scatter(&pi).save(POLICY_PLOT_FILE).unwrap();
+    plot::learning_curve(&history, SMOOTHING_WINDOW)
+        .save(LEARNING_CURVE_FILE)
+        .unwrap();
+    println!("{}, {}", POLICY_PLOT_FILE, LEARNING_CURVE_FILE);
 }
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::{
+    games::gridworld::Gridworld,
+    policy::UniformRandom,
+    render::Render,
+    ties::{TieBreaking, Ties, Tolerance},
+    value_iteration::{Termination, UpdateScheme, ValueIteration},
+};
+
+const THETA: f64 = 0.0001;
+/// The tasks are undiscounted, so policy evaluation gets a cap on its sweeps
+const MAX_SWEEPS: usize = 10_000;
+/// Values within this of the best are ties, as the values are only accurate to about `THETA`
+const TIES: Ties = Ties {
+    tolerance: Tolerance::Absolute(0.001),
+    breaking: TieBreaking::All,
+};
+
+fn main() {
+    {
+        // Figure 4.1: the equiprobable random policy and its greedy policy
+        let task = Gridworld::example_4_1();
+        let value_iteration = ValueIteration::new(Box::new(task.clone())).with_ties(TIES);
+        let mut v: HashMap<_, _> = task.state_space().map(|s| (s, 0.0)).collect();
+        value_iteration.evaluate_policy(&UniformRandom, THETA, Some(MAX_SWEEPS), &mut v);
+        let greedy: HashMap<_, _> = task
+            .state_space()
+            .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+            .collect();
+        println!("Example 4.1: v of the random policy");
+        print!("{}", task.render_values(&v));
+        println!("greedy policy");
+        print!("{}", task.render_policy(&greedy));
+    }
+    println!();
+    {
+        // Figure 3.5: v* and π*
+        let task = Gridworld::example_3_5();
+        let value_iteration = ValueIteration::new(Box::new(task.clone())).with_ties(TIES);
+        let mut v: HashMap<_, _> = task.state_space().map(|s| (s, 0.0)).collect();
+        let report = value_iteration.value_iteration(
+            Termination::Residual(THETA),
+            UpdateScheme::InPlace,
+            None,
+            &mut v,
+        );
+        let policy: HashMap<_, _> = task
+            .state_space()
+            .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+            .collect();
+        println!("Example 3.5: v* after {} sweeps", report.sweeps);
+        print!("{}", task.render_values(&v));
+        println!("π*");
+        print!("{}", task.render_policy(&policy));
+    }
+}
@@ -6,11 +6,20 @@ use std::{
 };
 
 use reinforcement_learning::{
-    games::jacks_car_rental::JacksCarRental, value_iteration::ValueIteration,
+    games::jacks_car_rental::JacksCarRental,
+    plot::{self, Chart, GridStyle},
+    render::Render,
+    value_iteration::{Termination, UpdateScheme, ValueIteration},
 };
 
 const VALUE_OUTPUT_FILE: &str = "jacks_car_rental.value.csv";
 const ACTION_OUTPUT_FILE: &str = "jacks_car_rental.action.csv";
+const POLICY_PLOT_FILE: &str = "jacks_car_rental.policy.html";
+const VALUE_PLOT_FILE: &str = "jacks_car_rental.value.html";
+/// $\theta$ of the policy evaluation steps of policy iteration
+const THETA: f64 = 1.0;
+/// Policy iteration stops after this many policies even if not stable
+const MAX_ITERATIONS: usize = 20;
 
 fn main() {
     let task = JacksCarRental;
@@ -19,7 +28,16 @@ fn main() {
     for s in value_iteration.task().state_space() {
         v.insert(s, 0.0);
     }
-    value_iteration.value_iteration(10.0, &mut v);
+    let report = value_iteration.value_iteration(
+        Termination::Residual(10.0),
+        UpdateScheme::InPlace,
+        None,
+        &mut v,
+    );
+    println!(
+        "sweeps: {}, elapsed: {:?}, suboptimality bound: {:?}",
+        report.sweeps, report.elapsed, report.suboptimality_bound
+    );
     {
         println!("(s, V(s))");
         if let Err(e) = fs::remove_file(path::Path::new(VALUE_OUTPUT_FILE)) {
@@ -50,4 +68,55 @@ fn main() {
             }
         }
     }
+
+    println!();
+    {
+        let policy = value_iteration
+            .task()
+            .state_space()
+            .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+            .collect();
+        print!("{}", JacksCarRental.render_policy(&policy));
+    }
+    println!();
+    {
+        // Figure 4.2: policy iteration from never moving any car
+        let never_move = value_iteration
+            .task()
+            .state_space()
+            .map(|s| (s, vec![0]))
+            .collect();
+        let mut v = HashMap::new();
+        for s in value_iteration.task().state_space() {
+            v.insert(s, 0.0);
+        }
+        let policies =
+            value_iteration.policy_iteration(never_move, THETA, None, Some(MAX_ITERATIONS), &mut v);
+        let coordinates = |s: &(u32, u32)| (s.1 as f64, s.0 as f64);
+        let panels: Vec<_> = policies
+            .iter()
+            .enumerate()
+            .map(|(k, policy)| {
+                let mut grid = plot::policy_grid(policy, coordinates, GridStyle::Contour);
+                grid.title = format!("\\pi_{}", k);
+                grid.x_label = "#cars at second location".to_string();
+                grid.y_label = "#cars at first location".to_string();
+                grid
+            })
+            .collect();
+        let panels: Vec<&dyn Chart> = panels.iter().map(|grid| grid as &dyn Chart).collect();
+        plot::save_panels(POLICY_PLOT_FILE, &panels, 3).unwrap();
+
+        let mut grid = plot::value_grid(&v, coordinates, GridStyle::Surface);
+        grid.title = format!("v_\\pi_{}", policies.len() - 1);
+        grid.x_label = "#cars at second location".to_string();
+        grid.y_label = "#cars at first location".to_string();
+        grid.save(VALUE_PLOT_FILE).unwrap();
+        println!(
+            "policy iteration steps: {}; {}, {}",
+            policies.len() - 1,
+            POLICY_PLOT_FILE,
+            VALUE_PLOT_FILE
+        );
+    }
 }
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::{
+    evaluation::evaluate,
+    exploration::Exploration,
+    games::maze::Maze,
+    plot::{self, Chart, Figure, Series},
+    policy::Greedy,
+    q_learning::{QLearning, QLearningState},
+    render::Render,
+    schedule::Schedule,
+    ties::Ties,
+};
+
+const EPSILON: f64 = 0.1;
+const ALPHA: f64 = 0.1;
+const SEED: u64 = 0;
+const NUM_EPISODES: usize = 50;
+const EVALUATION_EPISODES: usize = 10;
+const EVALUATION_MAX_STEPS: usize = 1000;
+const EVALUATION_SEED: u64 = 1;
+const CUMULATIVE_REWARD_PLOT_FILE: &str = "maze.cumulative_reward.html";
+
+fn main() {
+    let exploration = Exploration::EpsilonGreedy {
+        epsilon: Schedule::Constant(EPSILON),
+    };
+    {
+        // Figure 8.2, without planning
+        let q_learning = QLearning::new(Box::new(Maze::dyna()));
+        let mut state = QLearningState::new(SEED);
+        let history = q_learning.value_evaluation(
+            &mut state,
+            &exploration,
+            Schedule::Constant(ALPHA),
+            NUM_EPISODES,
+            None,
+        );
+        let lengths: Vec<String> = history
+            .episodes
+            .iter()
+            .map(|e| e.length.to_string())
+            .collect();
+        println!("Dyna Maze, steps per episode: {}", lengths.join(" "));
+        let q = state.value;
+        let policy: HashMap<_, _> = Maze::dyna()
+            .state_space()
+            .map(|s| (s, q_learning.max_value_by_actions(&q, &s).1))
+            .collect();
+        print!("{}", Maze::dyna().render_policy(&policy));
+        let greedy = Greedy {
+            value: &q,
+            ties: Ties::default(),
+        };
+        println!(
+            "{:#?}",
+            evaluate(
+                q_learning.task(),
+                &greedy,
+                EVALUATION_EPISODES,
+                Some(EVALUATION_MAX_STEPS),
+                EVALUATION_SEED
+            )
+        );
+    }
+    println!();
+
+    // Figures 8.4 and 8.5: cumulative reward over time steps, across the change of walls
+    let mut figure = Figure::new("Changing mazes", "Time steps", "Cumulative reward");
+    for (name, maze, total_steps) in [
+        ("Blocking Maze", Maze::blocking(1000), 3000),
+        ("Shortcut Maze", Maze::shortcut(3000), 6000),
+    ] {
+        let mut q_learning = QLearning::new(Box::new(maze));
+        let mut state = QLearningState::new(SEED);
+        let mut steps = 0;
+        let mut x = vec![0.0];
+        let mut y = vec![0.0];
+        while steps < total_steps {
+            // one episode at a time, cut off where the plot ends
+            q_learning = q_learning.with_max_steps(total_steps - steps);
+            let history = q_learning.value_evaluation(
+                &mut state,
+                &exploration,
+                Schedule::Constant(ALPHA),
+                1,
+                None,
+            );
+            let stats = history.episodes[0];
+            steps += stats.length;
+            x.push(steps as f64);
+            // the only reward is the +1 that ends each episode
+            let reward = if stats.truncated { 0.0 } else { 1.0 };
+            y.push(y.last().unwrap() + reward);
+        }
+        println!(
+            "{}: cumulative reward {} after {} steps",
+            name,
+            y.last().unwrap(),
+            steps
+        );
+        figure = figure.with_series(Series {
+            name: name.to_string(),
+            x,
+            y,
+            mode: plot::Mode::Lines,
+            band: None,
+        });
+    }
+    figure.save(CUMULATIVE_REWARD_PLOT_FILE).unwrap();
+    println!("{}", CUMULATIVE_REWARD_PLOT_FILE);
+}
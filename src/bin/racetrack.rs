@@ -0,0 +1,64 @@
+use reinforcement_learning::{
+    exploration::Exploration,
+    games::racetrack::Racetrack,
+    monte_carlo::{MonteCarlo, MonteCarloState},
+};
+
+const TRACKS: [&str; 2] = [
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tracks/figure_5_5_left.txt"),
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tracks/figure_5_5_right.txt"),
+];
+/// Greedy from $Q = -10$, about the length of a good run; the noise and the random starts explore
+const INITIAL_VALUE: f64 = -10.0;
+const NUM_EPISODES: usize = 20_000;
+const MAX_STEPS: usize = 1_000;
+const SEED: u64 = 0;
+const LAST_EPISODES: usize = 1_000;
+
+fn main() {
+    for path in TRACKS {
+        let track = Racetrack::load(path).unwrap();
+        let monte_carlo = MonteCarlo::new(Box::new(track.clone())).with_max_steps(MAX_STEPS);
+        let mut state = MonteCarloState::new(SEED);
+        let history = monte_carlo.policy_evaluation(
+            &mut state,
+            &Exploration::Optimistic {
+                initial_value: INITIAL_VALUE,
+            },
+            NUM_EPISODES,
+            None,
+        );
+        let returns = history.returns();
+        println!(
+            "{}: mean return of the last {} episodes {:.1}",
+            path,
+            LAST_EPISODES,
+            returns[returns.len() - LAST_EPISODES..].iter().sum::<f64>() / LAST_EPISODES as f64
+        );
+
+        // Exercise 5.12: optimal trajectories with the noise turned off
+        let track = track.with_noise(0.0);
+        let starts = track.starts();
+        for start in [
+            starts[0],
+            starts[starts.len() / 2],
+            starts[starts.len() - 1],
+        ] {
+            let trajectory = track.trajectory(&state.policy, Racetrack::start_state(start), 100);
+            let end = if track.is_finish(trajectory.last().unwrap()) {
+                "finishes"
+            } else {
+                // a crash costs no more than a step, and may restart somewhere better
+                "crashes"
+            };
+            println!(
+                "from {:?}, {} after {} steps",
+                start,
+                end,
+                trajectory.len() - 1
+            );
+            print!("{}", track.render_trajectory(&trajectory));
+        }
+        println!();
+    }
+}
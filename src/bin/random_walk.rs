@@ -0,0 +1,118 @@
+use reinforcement_learning::{
+    games::random_walk::RandomWalk,
+    plot::{self, Chart, Figure, Series},
+    prediction::{Method, Prediction},
+    render::table,
+};
+
+const RUNS: usize = 100;
+const SEED: u64 = 0;
+const RMS_PLOT_FILE: &str = "random_walk.rms.html";
+
+fn main() {
+    {
+        // Example 6.2: TD(0) against constant-α MC on the 5-state walk
+        const NUM_EPISODES: usize = 100;
+        const INITIAL_VALUE: f64 = 0.5;
+        let task = RandomWalk::five_states();
+        let true_value = task.true_value();
+        let prediction = Prediction::new(Box::new(task));
+        let mut figure = Figure::new(
+            "Empirical RMS error, averaged over states",
+            "Walks / Episodes",
+            "RMS error",
+        );
+        for (name, method, alpha) in [
+            ("TD α=0.15", Method::Td, 0.15),
+            ("TD α=0.1", Method::Td, 0.1),
+            ("TD α=0.05", Method::Td, 0.05),
+            ("MC α=0.01", Method::MonteCarlo, 0.01),
+            ("MC α=0.02", Method::MonteCarlo, 0.02),
+            ("MC α=0.03", Method::MonteCarlo, 0.03),
+            ("MC α=0.04", Method::MonteCarlo, 0.04),
+        ] {
+            let errors = prediction.benchmark(
+                method,
+                alpha,
+                &true_value,
+                INITIAL_VALUE,
+                NUM_EPISODES,
+                RUNS,
+                SEED,
+            );
+            println!(
+                "{}: RMS error after {} episodes {:.3}",
+                name, NUM_EPISODES, errors[NUM_EPISODES]
+            );
+            figure = figure.with_series(Series {
+                name: name.to_string(),
+                x: (0..errors.len()).map(|e| e as f64).collect(),
+                y: errors,
+                mode: plot::Mode::Lines,
+                band: None,
+            });
+        }
+        figure.save(RMS_PLOT_FILE).unwrap();
+        println!("{}", RMS_PLOT_FILE);
+    }
+    println!();
+    {
+        // Figures 7.2 and 12.6: RMS error over the first 10 episodes of the 19-state walk, by α
+        const NUM_EPISODES: usize = 10;
+        let task = RandomWalk::nineteen_states();
+        let true_value = task.true_value();
+        let prediction = Prediction::new(Box::new(task));
+        let alphas: Vec<f64> = (1..=10).map(|a| a as f64 / 10.0).collect();
+        let methods: Vec<(String, Method)> = [1, 2, 4, 8, 16]
+            .iter()
+            .map(|n| (format!("n={}", n), Method::NStepTd { n: *n }))
+            .chain([0.0, 0.4, 0.8, 0.9].iter().map(|lambda| {
+                (
+                    format!("λ={}", lambda),
+                    Method::TdLambda { lambda: *lambda },
+                )
+            }))
+            .collect();
+        let cells: Vec<Vec<String>> = methods
+            .iter()
+            .map(|(_, method)| {
+                alphas
+                    .iter()
+                    .map(|alpha| {
+                        let errors = prediction.benchmark(
+                            *method,
+                            *alpha,
+                            &true_value,
+                            0.0,
+                            NUM_EPISODES,
+                            RUNS,
+                            SEED,
+                        );
+                        let mean = errors[1..].iter().sum::<f64>() / NUM_EPISODES as f64;
+                        // accumulating traces diverge for large α and λ
+                        if mean < 1.0 {
+                            format!("{:.3}", mean)
+                        } else {
+                            ">1".to_string()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        println!(
+            "mean RMS error over the first {} episodes, by α",
+            NUM_EPISODES
+        );
+        print!(
+            "{}",
+            table(
+                &alphas.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+                &methods
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>(),
+                &cells,
+            )
+        );
+    }
+}
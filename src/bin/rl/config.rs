@@ -0,0 +1,134 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use clap::ValueEnum;
+use reinforcement_learning::{
+    exploration::Exploration,
+    schedule::Schedule,
+    sweep::{Params, Search},
+    value_iteration::{Termination, UpdateScheme},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Game {
+    Blackjack,
+    Gambler,
+    JacksCarRental,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    MonteCarlo,
+    QLearning,
+}
+
+/// Hyperparameters of a run, read from a TOML or JSON file and then overridden by flags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub game: Game,
+    /// Learner of `train`; `solve` always uses value iteration
+    pub algorithm: Algorithm,
+    pub seed: u64,
+    pub episodes: usize,
+    /// Save a checkpoint after every this many episodes
+    pub checkpoint_every: Option<usize>,
+    pub max_steps: Option<usize>,
+    /// $\alpha$ of Q-learning
+    pub alpha: Schedule,
+    pub exploration: Exploration,
+    pub termination: Termination,
+    pub scheme: UpdateScheme,
+    pub max_sweeps: Option<usize>,
+    pub evaluation_episodes: usize,
+    pub evaluation_seed: u64,
+    pub out: PathBuf,
+    pub sweep: Option<SweepConfig>,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            game: Game::Blackjack,
+            algorithm: Algorithm::QLearning,
+            seed: 0,
+            episodes: 10_000,
+            checkpoint_every: None,
+            max_steps: None,
+            alpha: Schedule::Constant(0.1),
+            exploration: Exploration::EpsilonGreedy {
+                epsilon: Schedule::Constant(0.1),
+            },
+            termination: Termination::Residual(0.01),
+            scheme: UpdateScheme::InPlace,
+            max_sweeps: None,
+            evaluation_episodes: 100_000,
+            evaluation_seed: 0,
+            out: PathBuf::from("out"),
+            sweep: None,
+        }
+    }
+}
+impl Config {
+    /// Read a `.toml` or `.json` file; missing fields take their defaults
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(toml::from_str(&text)?),
+            Some("json") => Ok(serde_json::from_str(&text)?),
+            _ => Err(format!("unknown config format: {}", path.display()).into()),
+        }
+    }
+
+    /// Override hyperparameters by name
+    ///
+    /// `epsilon`, `temperature`, `c` and `initial_value` each pick the exploration they belong to,
+    /// so at most one of them may be given.
+    pub fn with_params(&self, params: &Params) -> Result<Self, String> {
+        let explorations: Vec<&str> = ["epsilon", "temperature", "c", "initial_value"]
+            .into_iter()
+            .filter(|name| params.contains_key(*name))
+            .collect();
+        if 1 < explorations.len() {
+            return Err(format!(
+                "hyperparameters of different explorations: {}",
+                explorations.join(", ")
+            ));
+        }
+        let mut config = self.clone();
+        for (name, &v) in params {
+            match name.as_str() {
+                "alpha" => config.alpha = Schedule::Constant(v),
+                "epsilon" => {
+                    config.exploration = Exploration::EpsilonGreedy {
+                        epsilon: Schedule::Constant(v),
+                    }
+                }
+                "temperature" => {
+                    config.exploration = Exploration::Softmax {
+                        temperature: Schedule::Constant(v),
+                    }
+                }
+                "c" => config.exploration = Exploration::Ucb { c: v },
+                "initial_value" => {
+                    config.exploration = Exploration::Optimistic { initial_value: v }
+                }
+                "max_steps" => config.max_steps = Some(v as usize),
+                _ => return Err(format!("unknown hyperparameter: {}", name)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The `[sweep]` section of a config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    pub search: Search,
+    /// Number of seeds per configuration, counting up from `seed`
+    pub seeds: u64,
+    /// Defaults to the available parallelism
+    pub threads: Option<usize>,
+}
@@ -0,0 +1,624 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use reinforcement_learning::{
+    evaluation::{self, EvaluationReport},
+    exploration::Exploration,
+    games::{
+        blackjack::{self, Blackjack},
+        gambler::Gambler,
+        jacks_car_rental::JacksCarRental,
+    },
+    history::History,
+    monte_carlo::{MonteCarlo, MonteCarloState, MonteCarloTask},
+    plot::{self, Chart, GridStyle},
+    policy::Greedy,
+    q_learning::{QLearning, QLearningState, QLearningTask},
+    schedule::Schedule,
+    serialization::{
+        load_action_values, load_checkpoint, load_history, load_policy, load_state_values,
+        save_action_values, save_checkpoint, save_history, save_policy, save_state_values,
+        CheckpointHeader, Format,
+    },
+    sweep::{save_summary_table, sweep},
+    ties::Ties,
+    value_iteration::{Termination, ValueIteration, ValueIterationTask},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use config::{Algorithm, Config, Game};
+
+mod config;
+
+const CONFIG_FILE: &str = "config.toml";
+const CHECKPOINT_FILE: &str = "checkpoint.bin";
+const HISTORY_FILE: &str = "history.csv";
+const STATE_VALUE_FILE: &str = "value.csv";
+const ACTION_VALUE_FILE: &str = "action_value.csv";
+const POLICY_FILE: &str = "policy.csv";
+const SOLVE_REPORT_FILE: &str = "solve.json";
+const EVALUATION_REPORT_FILE: &str = "evaluation.json";
+const SWEEP_TABLE_FILE: &str = "sweep.csv";
+const SWEEP_SUMMARY_FILE: &str = "sweep.json";
+/// Window of the moving average of returns on learning curves
+const SMOOTHING_WINDOW: usize = 100;
+
+#[derive(Parser)]
+#[command(
+    name = "rl",
+    about = "Solve, train, evaluate and plot the sample games"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a game from its model by value iteration
+    Solve(Args),
+    /// Learn a game from sampled episodes, resuming a checkpoint of the same run in the output directory
+    Train(Args),
+    /// Evaluate a saved policy
+    Evaluate(Args),
+    /// Plot the tables and the training history in the output directory
+    Plot {
+        #[command(flatten)]
+        args: Args,
+        #[arg(long, value_enum, default_value = "html")]
+        image: Image,
+    },
+    /// Train and evaluate every configuration of the `[sweep]` section of the config, on several seeds each
+    Sweep(Args),
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Image {
+    /// Standalone HTML with plotly.js embedded, interactive in a browser even offline
+    Html,
+    Svg,
+}
+
+/// Flags override the values of the config file
+#[derive(clap::Args)]
+struct Args {
+    /// TOML or JSON file of hyperparameters
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long, value_enum)]
+    game: Option<Game>,
+    #[arg(long, value_enum)]
+    algorithm: Option<Algorithm>,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(long)]
+    episodes: Option<usize>,
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+    #[arg(long)]
+    max_steps: Option<usize>,
+    /// Constant step size
+    #[arg(long)]
+    alpha: Option<f64>,
+    /// Constant exploration rate of epsilon-greedy
+    #[arg(long)]
+    epsilon: Option<f64>,
+    /// Stop value iteration once the max residual of a sweep falls below this
+    #[arg(long)]
+    theta: Option<f64>,
+    #[arg(long)]
+    evaluation_episodes: Option<usize>,
+    #[arg(long)]
+    evaluation_seed: Option<u64>,
+    /// Policy file to evaluate; defaults to the one in the output directory
+    #[arg(long)]
+    policy: Option<PathBuf>,
+    /// Output directory
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+impl Args {
+    fn config(&self) -> Result<Config, Box<dyn Error>> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        if let Some(game) = self.game {
+            config.game = game;
+        }
+        if let Some(algorithm) = self.algorithm {
+            config.algorithm = algorithm;
+        }
+        if let Some(seed) = self.seed {
+            config.seed = seed;
+        }
+        if let Some(episodes) = self.episodes {
+            config.episodes = episodes;
+        }
+        if let Some(checkpoint_every) = self.checkpoint_every {
+            config.checkpoint_every = Some(checkpoint_every);
+        }
+        if let Some(max_steps) = self.max_steps {
+            config.max_steps = Some(max_steps);
+        }
+        if let Some(alpha) = self.alpha {
+            config.alpha = Schedule::Constant(alpha);
+        }
+        if let Some(epsilon) = self.epsilon {
+            config.exploration = Exploration::EpsilonGreedy {
+                epsilon: Schedule::Constant(epsilon),
+            };
+        }
+        if let Some(theta) = self.theta {
+            config.termination = Termination::Residual(theta);
+        }
+        if let Some(evaluation_episodes) = self.evaluation_episodes {
+            config.evaluation_episodes = evaluation_episodes;
+        }
+        if let Some(evaluation_seed) = self.evaluation_seed {
+            config.evaluation_seed = evaluation_seed;
+        }
+        if let Some(out) = &self.out {
+            config.out = out.clone();
+        }
+        Ok(config)
+    }
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let (Command::Solve(args)
+    | Command::Train(args)
+    | Command::Evaluate(args)
+    | Command::Plot { args, .. }
+    | Command::Sweep(args)) = &cli.command;
+    let config = args.config()?;
+    fs::create_dir_all(&config.out)?;
+
+    match &cli.command {
+        Command::Solve(_) => {
+            fs::write(config.out.join(CONFIG_FILE), toml::to_string(&config)?)?;
+            match config.game {
+                Game::Gambler => solve(Box::new(Gambler), &config),
+                Game::JacksCarRental => solve(Box::new(JacksCarRental), &config),
+                Game::Blackjack => Err("blackjack has no model to solve".into()),
+            }
+        }
+        Command::Train(_) => match (config.game, config.algorithm) {
+            (Game::Blackjack, Algorithm::MonteCarlo) => {
+                train_monte_carlo(Box::new(Blackjack), &config)
+            }
+            (Game::Blackjack, Algorithm::QLearning) => {
+                train_q_learning(Box::new(Blackjack), &config)
+            }
+            (Game::Gambler, Algorithm::MonteCarlo) => train_monte_carlo(Box::new(Gambler), &config),
+            (game, algorithm) => {
+                Err(format!("{:?} cannot be trained by {:?}", game, algorithm).into())
+            }
+        },
+        Command::Evaluate(_) => {
+            let policy = args
+                .policy
+                .clone()
+                .unwrap_or_else(|| config.out.join(POLICY_FILE));
+            match config.game {
+                Game::Blackjack => evaluate(Box::new(Blackjack), &config, &policy),
+                Game::Gambler => evaluate(Box::new(Gambler), &config, &policy),
+                Game::JacksCarRental => Err("jacks_car_rental cannot be sampled".into()),
+            }
+        }
+        Command::Plot { image, .. } => plot(&config, *image),
+        Command::Sweep(_) => {
+            fs::write(config.out.join(CONFIG_FILE), toml::to_string(&config)?)?;
+            run_sweep(&config)
+        }
+    }
+}
+
+fn run_sweep(config: &Config) -> Result<(), Box<dyn Error>> {
+    let spec = config
+        .sweep
+        .as_ref()
+        .ok_or("the config has no [sweep] section")?;
+    if !matches!(
+        (config.game, config.algorithm),
+        (Game::Blackjack, _) | (Game::Gambler, Algorithm::MonteCarlo)
+    ) {
+        return Err(format!(
+            "{:?} cannot be trained by {:?}",
+            config.game, config.algorithm
+        )
+        .into());
+    }
+    let configurations = spec.search.configurations()?;
+    for params in &configurations {
+        config.with_params(params)?;
+    }
+    let seeds: Vec<u64> = (config.seed..config.seed + spec.seeds).collect();
+    let threads = spec.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+    println!(
+        "{} configurations x {} seeds on {} threads",
+        configurations.len(),
+        seeds.len(),
+        threads
+    );
+
+    let summaries = sweep(&configurations, &seeds, threads, |params, seed| {
+        let config = config.with_params(params).unwrap();
+        match (config.game, config.algorithm) {
+            (Game::Blackjack, Algorithm::MonteCarlo) => {
+                run_monte_carlo(Box::new(Blackjack), &config, seed)
+            }
+            (Game::Blackjack, Algorithm::QLearning) => {
+                run_q_learning(Box::new(Blackjack), &config, seed)
+            }
+            (Game::Gambler, Algorithm::MonteCarlo) => {
+                run_monte_carlo(Box::new(Gambler), &config, seed)
+            }
+            _ => unreachable!(),
+        }
+    });
+    for summary in &summaries {
+        println!(
+            "{:?}: evaluation {:.4} ± {:.4}, training {:.4}, final {:.4}",
+            summary.params,
+            summary.evaluation_mean,
+            summary.evaluation_std_error,
+            summary.training_mean,
+            summary.training_final
+        );
+    }
+    save_summary_table(config.out.join(SWEEP_TABLE_FILE), &summaries)?;
+    write_json(config.out.join(SWEEP_SUMMARY_FILE), &summaries)
+}
+
+/// Train from scratch on `seed` and evaluate the greedy policy
+fn run_q_learning<State, Action>(
+    task: Box<dyn QLearningTask<State, Action>>,
+    config: &Config,
+    seed: u64,
+) -> (History, EvaluationReport)
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
+{
+    let mut q_learning = QLearning::new(task);
+    if let Some(max_steps) = config.max_steps {
+        q_learning = q_learning.with_max_steps(max_steps);
+    }
+    let mut state = QLearningState::new(seed);
+    let history = q_learning.value_evaluation(
+        &mut state,
+        &config.exploration,
+        config.alpha,
+        config.episodes,
+        None,
+    );
+    let greedy = Greedy {
+        value: &state.value,
+        ties: Ties::default(),
+    };
+    let report = evaluation::evaluate(
+        q_learning.task(),
+        &greedy,
+        config.evaluation_episodes,
+        config.max_steps,
+        config.evaluation_seed,
+    );
+    (history, report)
+}
+
+/// Train from scratch on `seed` and evaluate the target policy
+fn run_monte_carlo<State, Action>(
+    task: Box<dyn MonteCarloTask<State, Action>>,
+    config: &Config,
+    seed: u64,
+) -> (History, EvaluationReport)
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
+{
+    let mut monte_carlo = MonteCarlo::new(task);
+    if let Some(max_steps) = config.max_steps {
+        monte_carlo = monte_carlo.with_max_steps(max_steps);
+    }
+    let mut state = MonteCarloState::new(seed);
+    let history =
+        monte_carlo.policy_evaluation(&mut state, &config.exploration, config.episodes, None);
+    let report = evaluation::evaluate(
+        monte_carlo.task(),
+        &state.policy,
+        config.evaluation_episodes,
+        config.max_steps,
+        config.evaluation_seed,
+    );
+    (history, report)
+}
+
+fn solve<State, Action>(
+    task: Box<dyn ValueIterationTask<State, Action>>,
+    config: &Config,
+) -> Result<(), Box<dyn Error>>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + Serialize,
+    Action: Copy + std::cmp::Ord + Serialize,
+{
+    let value_iteration = ValueIteration::new(task).with_seed(config.seed);
+    let mut v: HashMap<State, f64> = value_iteration
+        .task()
+        .state_space()
+        .map(|s| (s, 0.0))
+        .collect();
+    let report = value_iteration.value_iteration(
+        config.termination,
+        config.scheme,
+        config.max_sweeps,
+        &mut v,
+    );
+    println!(
+        "sweeps: {}, elapsed: {:?}, converged: {}, suboptimality bound: {:?}",
+        report.sweeps, report.elapsed, report.converged, report.suboptimality_bound
+    );
+    let policy: HashMap<State, Vec<Action>> = value_iteration
+        .task()
+        .state_space()
+        .map(|s| (s, value_iteration.max_v_a(&v, &s).1))
+        .collect();
+
+    save_state_values(config.out.join(STATE_VALUE_FILE), Format::Csv, &v)?;
+    save_policy(config.out.join(POLICY_FILE), Format::Csv, &policy)?;
+    write_json(config.out.join(SOLVE_REPORT_FILE), &report)
+}
+
+fn train_q_learning<State, Action>(
+    task: Box<dyn QLearningTask<State, Action>>,
+    config: &Config,
+) -> Result<(), Box<dyn Error>>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + Serialize + DeserializeOwned,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + Serialize + DeserializeOwned + 'static,
+{
+    let mut q_learning = QLearning::new(task);
+    if let Some(max_steps) = config.max_steps {
+        q_learning = q_learning.with_max_steps(max_steps);
+    }
+    let state = train_in_chunks(
+        config,
+        || QLearningState::new(config.seed),
+        |state| state.episode,
+        |state, num_episodes| {
+            q_learning.value_evaluation(
+                state,
+                &config.exploration,
+                config.alpha,
+                num_episodes,
+                None,
+            )
+        },
+    )?;
+
+    let states: HashSet<State> = state.value.keys().map(|sa| sa.state).collect();
+    let policy: HashMap<State, Vec<Action>> = states
+        .into_iter()
+        .map(|s| (s, q_learning.max_value_by_actions(&state.value, &s).1))
+        .collect();
+    save_action_values(
+        config.out.join(ACTION_VALUE_FILE),
+        Format::Csv,
+        &state.value,
+    )?;
+    save_policy(config.out.join(POLICY_FILE), Format::Csv, &policy)?;
+    Ok(())
+}
+
+fn train_monte_carlo<State, Action>(
+    task: Box<dyn MonteCarloTask<State, Action>>,
+    config: &Config,
+) -> Result<(), Box<dyn Error>>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + Serialize + DeserializeOwned,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + Serialize + DeserializeOwned + 'static,
+{
+    let mut monte_carlo = MonteCarlo::new(task);
+    if let Some(max_steps) = config.max_steps {
+        monte_carlo = monte_carlo.with_max_steps(max_steps);
+    }
+    let state = train_in_chunks(
+        config,
+        || MonteCarloState::new(config.seed),
+        |state| state.episode,
+        |state, num_episodes| {
+            monte_carlo.policy_evaluation(state, &config.exploration, num_episodes, None)
+        },
+    )?;
+
+    save_action_values(
+        config.out.join(ACTION_VALUE_FILE),
+        Format::Csv,
+        &state.value,
+    )?;
+    save_policy(config.out.join(POLICY_FILE), Format::Csv, &state.policy)?;
+    Ok(())
+}
+
+/// Run up to `config.episodes` episodes in total, saving a checkpoint and the history after each chunk
+///
+/// A checkpoint left in the output directory by an earlier run is resumed if it was trained on the
+/// same game, algorithm and hyperparameters. The config is written once the checkpoint checks out.
+fn train_in_chunks<T>(
+    config: &Config,
+    new: impl FnOnce() -> T,
+    episode: impl Fn(&T) -> usize,
+    mut run: impl FnMut(&mut T, usize) -> History,
+) -> Result<T, Box<dyn Error>>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let checkpoint = config.out.join(CHECKPOINT_FILE);
+    let history_path = config.out.join(HISTORY_FILE);
+    let header = checkpoint_header(config);
+    let (mut state, mut history) = if checkpoint.exists() {
+        let state = load_checkpoint(&checkpoint, Format::Binary, &header)?;
+        let history = if history_path.exists() {
+            load_history(&history_path, Format::Csv)?
+        } else {
+            History::default()
+        };
+        (state, history)
+    } else {
+        (new(), History::default())
+    };
+    fs::write(config.out.join(CONFIG_FILE), toml::to_string(config)?)?;
+    if config.episodes <= episode(&state) {
+        println!(
+            "nothing to train: the checkpoint has {} episodes already, of {} asked for",
+            episode(&state),
+            config.episodes
+        );
+    }
+    while episode(&state) < config.episodes {
+        let num_episodes = config
+            .checkpoint_every
+            .unwrap_or(usize::MAX)
+            .min(config.episodes - episode(&state));
+        let chunk = run(&mut state, num_episodes);
+        history.episodes.extend(chunk.episodes);
+        save_checkpoint(&checkpoint, Format::Binary, &header, &state)?;
+        save_history(&history_path, Format::Csv, &history)?;
+    }
+    println!(
+        "episodes: {}, truncated: {}",
+        history.episodes.len(),
+        history.num_truncated()
+    );
+    Ok(state)
+}
+
+/// Everything of `config` a learner state depends on, except for the number of episodes
+fn checkpoint_header(config: &Config) -> CheckpointHeader {
+    let alpha = (config.algorithm == Algorithm::QLearning).then_some(config.alpha);
+    CheckpointHeader::new(
+        format!("{:?} {:?}", config.game, config.algorithm),
+        &serde_json::json!({
+            "seed": config.seed,
+            "max_steps": config.max_steps,
+            "alpha": alpha,
+            "exploration": config.exploration,
+        }),
+    )
+}
+
+fn evaluate<State, Action>(
+    task: Box<dyn MonteCarloTask<State, Action>>,
+    config: &Config,
+    policy: &Path,
+) -> Result<(), Box<dyn Error>>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + DeserializeOwned,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + DeserializeOwned + 'static,
+{
+    let format = Format::from_path(policy)
+        .ok_or_else(|| format!("unknown policy format: {}", policy.display()))?;
+    let policy: HashMap<State, Vec<Action>> = load_policy(policy, format)?;
+    let report = evaluation::evaluate(
+        task.as_ref(),
+        &policy,
+        config.evaluation_episodes,
+        config.max_steps,
+        config.evaluation_seed,
+    );
+    println!("{:#?}", report);
+    write_json(config.out.join(EVALUATION_REPORT_FILE), &report)
+}
+
+fn plot(config: &Config, image: Image) -> Result<(), Box<dyn Error>> {
+    let extension = match image {
+        Image::Html => "html",
+        Image::Svg => "svg",
+    };
+    let save_panels = |panels: &[&dyn Chart], name: &str| -> Result<(), Box<dyn Error>> {
+        let path = config.out.join(name).with_extension(extension);
+        plot::save_panels(&path, panels, 2)?;
+        println!("{}", path.display());
+        Ok(())
+    };
+    let save = |chart: &dyn Chart, name: &str| save_panels(&[chart], name);
+
+    let history = config.out.join(HISTORY_FILE);
+    if history.exists() {
+        let history = load_history(&history, Format::Csv)?;
+        save(
+            &plot::learning_curve(&history, SMOOTHING_WINDOW),
+            "learning_curve",
+        )?;
+    }
+    let value = config.out.join(STATE_VALUE_FILE);
+    let policy = config.out.join(POLICY_FILE);
+    match config.game {
+        Game::Gambler => {
+            if value.exists() {
+                let v: HashMap<i32, f64> = load_state_values(&value, Format::Csv)?;
+                save(&plot::value_curve(&v), "value")?;
+            }
+            if policy.exists() {
+                let policy: HashMap<i32, Vec<i32>> = load_policy(&policy, Format::Csv)?;
+                save(&plot::policy_scatter(&policy), "policy")?;
+            }
+        }
+        Game::JacksCarRental => {
+            let coordinates = |s: &(u32, u32)| (s.1 as f64, s.0 as f64);
+            if value.exists() {
+                let v: HashMap<(u32, u32), f64> = load_state_values(&value, Format::Csv)?;
+                save(
+                    &plot::value_grid(&v, coordinates, GridStyle::Surface),
+                    "value",
+                )?;
+            }
+            if policy.exists() {
+                let policy: HashMap<(u32, u32), Vec<i32>> = load_policy(&policy, Format::Csv)?;
+                save(
+                    &plot::policy_grid(&policy, coordinates, GridStyle::Heatmap),
+                    "policy",
+                )?;
+            }
+        }
+        Game::Blackjack => {
+            let value = config.out.join(ACTION_VALUE_FILE);
+            if value.exists() {
+                let q = load_action_values(&value, Format::Csv)?;
+                let [a, b] = blackjack::value_surfaces(&q);
+                save_panels(&[&a, &b], "value")?;
+            }
+            if policy.exists() {
+                let policy = load_policy(&policy, Format::Csv)?;
+                let [a, b] = blackjack::policy_grids(&policy);
+                save_panels(&[&a, &b], "policy")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_json<T>(path: PathBuf, value: &T) -> Result<(), Box<dyn Error>>
+where
+    T: Serialize,
+{
+    serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), value)?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use reinforcement_learning::{
+    exploration::Exploration,
+    games::{gridworld::Connectivity, windy_gridworld::WindyGridworld},
+    q_learning::{QLearning, QLearningState},
+    render::Render,
+    schedule::Schedule,
+    value_iteration::{Termination, UpdateScheme, ValueIteration},
+};
+
+const EPSILON: f64 = 0.1;
+const ALPHA: f64 = 0.5;
+const NUM_EPISODES: usize = 200;
+const SEED: u64 = 0;
+const START: (usize, usize) = (3, 0);
+
+fn main() {
+    // Example 6.5 and Exercises 6.9 and 6.10
+    let variants = [
+        ("4 moves", WindyGridworld::new()),
+        (
+            "King's moves",
+            WindyGridworld::new().with_connectivity(Connectivity::Eight),
+        ),
+        (
+            "King's moves and staying put",
+            WindyGridworld::new().with_connectivity(Connectivity::Nine),
+        ),
+        (
+            "King's moves, stochastic wind",
+            WindyGridworld::new()
+                .with_connectivity(Connectivity::Eight)
+                .with_stochastic_wind(),
+        ),
+    ];
+    for (name, task) in variants {
+        let mut v: HashMap<_, _> = task.state_space().map(|s| (s, 0.0)).collect();
+        let value_iteration = ValueIteration::new(Box::new(task));
+        value_iteration.value_iteration(
+            Termination::Residual(0.01),
+            UpdateScheme::InPlace,
+            None,
+            &mut v,
+        );
+        println!("{}: expected steps from the start {:.1}", name, -v[&START]);
+    }
+    println!();
+
+    let task = WindyGridworld::new();
+    let q_learning = QLearning::new(Box::new(WindyGridworld::new()));
+    let mut state = QLearningState::new(SEED);
+    let history = q_learning.value_evaluation(
+        &mut state,
+        &Exploration::EpsilonGreedy {
+            epsilon: Schedule::Constant(EPSILON),
+        },
+        Schedule::Constant(ALPHA),
+        NUM_EPISODES,
+        None,
+    );
+    let steps: usize = history.episodes.iter().map(|e| e.length).sum();
+    println!(
+        "Q-learning: {} episodes in {} steps, last one {} steps",
+        NUM_EPISODES,
+        steps,
+        history.episodes.last().unwrap().length
+    );
+    let q = state.value;
+    let policy: HashMap<_, _> = task
+        .state_space()
+        .map(|s| (s, q_learning.max_value_by_actions(&q, &s).1))
+        .collect();
+    print!("{}", task.render_policy(&policy));
+}
@@ -0,0 +1,144 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{monte_carlo::MonteCarloTask, policy::Policy, q_learning::QLearningTask};
+
+/// A task episodes can be sampled from, whichever learner it was written for
+pub trait Environment<State, Action> {
+    fn gamma(&self) -> f64;
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>>;
+    fn random_state(&self, rng: &mut dyn RngCore) -> State;
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64);
+    fn in_terminal_state_space(&self, s: &State) -> bool;
+}
+
+impl<State, Action> Environment<State, Action> for dyn MonteCarloTask<State, Action> + '_ {
+    fn gamma(&self) -> f64 {
+        MonteCarloTask::gamma(self)
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        MonteCarloTask::action_space(self, s)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        MonteCarloTask::random_state(self, rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        MonteCarloTask::transit(self, s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        MonteCarloTask::in_terminal_state_space(self, s)
+    }
+}
+
+impl<State, Action> Environment<State, Action> for dyn QLearningTask<State, Action> + '_ {
+    fn gamma(&self) -> f64 {
+        QLearningTask::gamma(self)
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        QLearningTask::action_space(self, s)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        QLearningTask::random_state(self, rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        QLearningTask::transit(self, s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        QLearningTask::in_terminal_state_space(self, s)
+    }
+}
+
+/// Run `policy` on `environment` for `num_episodes` episodes on an RNG of its own seeded by `seed`
+///
+/// - `max_steps`: cut episodes off after this many steps, counting them as truncated
+pub fn evaluate<State, Action, E>(
+    environment: &E,
+    policy: &dyn Policy<State, Action>,
+    num_episodes: usize,
+    max_steps: Option<usize>,
+    seed: u64,
+) -> EvaluationReport
+where
+    Action: Copy + std::cmp::Eq + 'static,
+    E: Environment<State, Action> + ?Sized,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let gamma = environment.gamma();
+    let mut returns = vec![];
+    let mut truncated = 0;
+    for _ in 0..num_episodes {
+        let mut s = environment.random_state(&mut rng);
+        let mut ret = 0.0;
+        let mut discount = 1.0;
+        let mut length = 0;
+        while !environment.in_terminal_state_space(&s) {
+            if max_steps.is_some_and(|max| length >= max) {
+                truncated += 1;
+                break;
+            }
+            let a = policy.sample(&s, environment.action_space(&s), &mut rng);
+            let (s_next, r) = environment.transit(&s, &a, &mut rng);
+            ret += discount * r;
+            discount *= gamma;
+            length += 1;
+            s = s_next;
+        }
+        returns.push(ret);
+    }
+    EvaluationReport::from_returns(&returns, truncated)
+}
+
+/// Summary of the returns of a policy over many episodes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub episodes: usize,
+    pub mean: f64,
+    pub std_error: f64,
+    /// 95% confidence interval of the mean under the normal approximation
+    pub ci95: (f64, f64),
+    /// Episodes with a positive return, i.e. wins in Blackjack
+    pub wins: usize,
+    /// Episodes with a zero return, i.e. draws in Blackjack
+    pub draws: usize,
+    /// Episodes with a negative return, i.e. losses in Blackjack
+    pub losses: usize,
+    /// Episodes cut off by the step limit
+    pub truncated: usize,
+}
+
+impl EvaluationReport {
+    /// With fewer than two returns there is no spread to estimate: `std_error` is zero and
+    /// `ci95` collapses onto the mean, which is zero without any returns.
+    pub fn from_returns(returns: &[f64], truncated: usize) -> Self {
+        let n = returns.len() as f64;
+        let mean = if returns.is_empty() {
+            0.0
+        } else {
+            returns.iter().sum::<f64>() / n
+        };
+        let std_error = if returns.len() < 2 {
+            0.0
+        } else {
+            let variance = returns.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            f64::sqrt(variance / n)
+        };
+        Self {
+            episodes: returns.len(),
+            mean,
+            std_error,
+            ci95: (mean - 1.96 * std_error, mean + 1.96 * std_error),
+            wins: returns.iter().filter(|x| **x > 0.0).count(),
+            draws: returns.iter().filter(|x| **x == 0.0).count(),
+            losses: returns.iter().filter(|x| **x < 0.0).count(),
+            truncated,
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    policy::{EpsilonGreedy, Greedy, Policy, Softmax, Ucb},
+    schedule::Schedule,
+    ties::Ties,
+    StateActionPair,
+};
+
+/// How a learner picks its behavior policy from what it has learned so far
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exploration {
+    EpsilonGreedy {
+        /// $\epsilon$
+        epsilon: Schedule,
+    },
+    /// Boltzmann exploration
+    Softmax {
+        /// $\tau$
+        temperature: Schedule,
+    },
+    /// Count-based bonus on $N(s, a)$; see [`Ucb`]
+    Ucb { c: f64 },
+    /// Greedy, with every unseen $Q(s, a)$ initialized to `initial_value`
+    Optimistic { initial_value: f64 },
+}
+
+impl Exploration {
+    /// The behavior policy for the `episode`-th episode, counting from zero
+    pub fn behavior<'a, State, Action>(
+        &self,
+        value: &'a HashMap<StateActionPair<State, Action>, f64>,
+        visits: &'a HashMap<StateActionPair<State, Action>, usize>,
+        ties: Ties,
+        episode: usize,
+    ) -> Box<dyn Policy<State, Action> + 'a>
+    where
+        State: Copy + std::hash::Hash + std::cmp::Eq,
+        Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
+    {
+        let greedy = Greedy { value, ties };
+        match *self {
+            Exploration::EpsilonGreedy { epsilon } => Box::new(EpsilonGreedy {
+                greedy,
+                epsilon: epsilon.value(episode, 0),
+            }),
+            Exploration::Softmax { temperature } => Box::new(Softmax {
+                value,
+                temperature: temperature.value(episode, 0),
+            }),
+            Exploration::Ucb { c } => Box::new(Ucb {
+                value,
+                visits,
+                c,
+                ties,
+            }),
+            Exploration::Optimistic { .. } => Box::new(greedy),
+        }
+    }
+
+    /// $\epsilon$ or $\tau$ on the `episode`-th episode, for strategies that have one
+    pub fn parameter(&self, episode: usize) -> Option<f64> {
+        match self {
+            Exploration::EpsilonGreedy { epsilon } => Some(epsilon.value(episode, 0)),
+            Exploration::Softmax { temperature } => Some(temperature.value(episode, 0)),
+            Exploration::Ucb { .. } | Exploration::Optimistic { .. } => None,
+        }
+    }
+
+    /// Fill in $Q(s, \cdot)$ with the optimistic initial value, if any
+    pub fn initialize<State, Action>(
+        &self,
+        value: &mut HashMap<StateActionPair<State, Action>, f64>,
+        s: &State,
+        action_space: impl Iterator<Item = Action>,
+    ) where
+        State: Copy + std::hash::Hash + std::cmp::Eq,
+        Action: Copy + std::hash::Hash + std::cmp::Eq,
+    {
+        if let Exploration::Optimistic { initial_value } = *self {
+            for a in action_space {
+                value
+                    .entry(StateActionPair {
+                        state: *s,
+                        action: a,
+                    })
+                    .or_insert(initial_value);
+            }
+        }
+    }
+}
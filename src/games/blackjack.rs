@@ -1,6 +1,15 @@
-use rand::{seq::SliceRandom, Rng};
+use std::collections::HashMap;
 
-use crate::{monte_carlo::MonteCarloTask, q_learning::QLearningTask};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    monte_carlo::MonteCarloTask,
+    plot::{Grid, GridStyle},
+    q_learning::QLearningTask,
+    render::{max_over_actions, table, Render},
+    StateActionPair,
+};
 
 const GAMMA: f64 = 1.0;
 
@@ -15,8 +24,7 @@ impl Blackjack {
         2
     }
 
-    fn random_action(&self, _s: &State) -> Action {
-        let mut rng = rand::thread_rng();
+    fn random_action(&self, _s: &State, rng: &mut dyn RngCore) -> Action {
         let a = rng.gen_range(0..2);
         match a {
             0 => Action::Hit,
@@ -25,19 +33,18 @@ impl Blackjack {
         }
     }
 
-    fn random_state(&self) -> State {
-        let mut rng = rand::thread_rng();
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
         State {
-            dealer: gen_card(),
+            dealer: gen_card(rng),
             me: rng.gen_range(12..21 + 1),
             useful_ace: rng.gen(),
             after_stick: false,
         }
     }
 
-    fn transit(&self, s: &State, a: &Action) -> (State, f64) {
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
         let mut s_next = *s;
-        let card = gen_card();
+        let card = gen_card(rng);
         let r = match a {
             Action::Hit => s_next.me_get_card(card),
             Action::Stick => s_next.dealer_get_card(card),
@@ -68,16 +75,16 @@ impl MonteCarloTask<State, Action> for Blackjack {
         self.action_space_len(s)
     }
 
-    fn random_action(&self, s: &State) -> Action {
-        self.random_action(s)
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
     }
 
-    fn random_state(&self) -> State {
-        self.random_state()
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
     }
 
-    fn transit(&self, s: &State, a: &Action) -> (State, f64) {
-        self.transit(s, a)
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
     }
 
     fn in_terminal_state_space(&self, s: &State) -> bool {
@@ -97,16 +104,16 @@ impl QLearningTask<State, Action> for Blackjack {
         self.action_space_len(s)
     }
 
-    fn random_action(&self, s: &State) -> Action {
-        self.random_action(s)
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
     }
 
-    fn random_state(&self) -> State {
-        self.random_state()
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
     }
 
-    fn transit(&self, s: &State, a: &Action) -> (State, f64) {
-        self.transit(s, a)
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
     }
 
     fn in_terminal_state_space(&self, s: &State) -> bool {
@@ -114,13 +121,123 @@ impl QLearningTask<State, Action> for Blackjack {
     }
 }
 
-fn gen_card() -> u32 {
+/// Figure 5.2 layout: player sum from 21 down by dealer showing, with and without a usable ace
+impl Render<State, Action> for Blackjack {
+    /// `H`it or `S`tick; `?` where both tie, `.` where unseen
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        render_tables(|s| match policy.get(s).map(Vec::as_slice) {
+            Some([Action::Hit]) => "H".to_string(),
+            Some([Action::Stick]) => "S".to_string(),
+            Some([]) | None => ".".to_string(),
+            Some(_) => "?".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        render_tables(|s| match value.get(s) {
+            Some(v) => format!("{:.2}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+
+fn render_tables(cell: impl Fn(&State) -> String) -> String {
+    let dealer = [11, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    let me: Vec<u32> = (12..=21).rev().collect();
+    let mut text = String::new();
+    for useful_ace in [true, false] {
+        text += if useful_ace {
+            "usable ace\n"
+        } else {
+            "no usable ace\n"
+        };
+        let cells: Vec<Vec<String>> = me
+            .iter()
+            .map(|me| {
+                dealer
+                    .iter()
+                    .map(|dealer| {
+                        cell(&State {
+                            dealer: *dealer,
+                            me: *me,
+                            useful_ace,
+                            after_stick: false,
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        let columns: Vec<String> = dealer
+            .iter()
+            .map(|d| match d {
+                11 => "A".to_string(),
+                d => d.to_string(),
+            })
+            .collect();
+        text += &table(
+            &columns,
+            &me.iter().map(u32::to_string).collect::<Vec<_>>(),
+            &cells,
+        );
+    }
+    text
+}
+
+/// Figure 5.2: $V(s) = \max_a Q(s, a)$ over (dealer showing, player sum), with and without a usable ace
+pub fn value_surfaces(value: &HashMap<StateActionPair<State, Action>, f64>) -> [Grid; 2] {
+    let v = max_over_actions(value);
+    [true, false].map(|useful_ace| {
+        let points = v
+            .iter()
+            .filter(|(s, _)| decision_state(s) && s.useful_ace == useful_ace)
+            .map(|(s, v)| (dealer_showing(s), s.me as f64, *v));
+        grid(useful_ace, points, GridStyle::Surface)
+    })
+}
+
+/// Figure 5.2: where $\pi$ sticks (1) or hits (0), with and without a usable ace; ties show as 0.5
+pub fn policy_grids(policy: &HashMap<State, Vec<Action>>) -> [Grid; 2] {
+    [true, false].map(|useful_ace| {
+        let points = policy
+            .iter()
+            .filter(|(s, a)| decision_state(s) && s.useful_ace == useful_ace && !a.is_empty())
+            .map(|(s, a)| {
+                let stick = a.iter().filter(|a| **a == Action::Stick).count();
+                let stick = stick as f64 / a.len() as f64;
+                (dealer_showing(s), s.me as f64, stick)
+            });
+        grid(useful_ace, points, GridStyle::Heatmap)
+    })
+}
+
+fn decision_state(s: &State) -> bool {
+    !s.after_stick && (12..=21).contains(&s.me)
+}
+
+/// Aces show as 1, as in the book
+fn dealer_showing(s: &State) -> f64 {
+    match s.dealer {
+        11 => 1.0,
+        card => card as f64,
+    }
+}
+
+fn grid(useful_ace: bool, points: impl Iterator<Item = (f64, f64, f64)>, style: GridStyle) -> Grid {
+    let title = if useful_ace {
+        "Usable ace"
+    } else {
+        "No usable ace"
+    };
+    Grid::from_points(title, "Dealer showing", "Player sum", points, style)
+}
+
+fn gen_card(rng: &mut dyn RngCore) -> u32 {
     *[2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10, 11]
-        .choose(&mut rand::thread_rng())
+        .choose(rng)
         .unwrap()
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct State {
     pub dealer: u32,
     pub me: u32,
@@ -171,7 +288,7 @@ impl State {
     }
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Action {
     Hit,
     Stick,
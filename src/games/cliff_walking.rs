@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, RngCore};
+
+use crate::{
+    games::gridworld::{arrows, render_cells, Cell, Connectivity, Move},
+    monte_carlo::MonteCarloTask,
+    q_learning::QLearningTask,
+    render::Render,
+    value_iteration::{Possibility, ValueIterationTask},
+};
+
+const GAMMA: f64 = 1.0;
+const HEIGHT: usize = 4;
+const WIDTH: usize = 12;
+const START: Cell = (3, 0);
+const GOAL: Cell = (3, 11);
+const STEP_REWARD: f64 = -1.0;
+const CLIFF_REWARD: f64 = -100.0;
+
+/// Example 6.6: $-1$ per move, and stepping into the cliff along the bottom costs $-100$ and
+/// sends the agent back to the start
+pub struct CliffWalking;
+impl CliffWalking {
+    pub fn is_cliff(&self, cell: &Cell) -> bool {
+        cell.0 == HEIGHT - 1 && 0 < cell.1 && cell.1 < WIDTH - 1
+    }
+
+    pub fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        let states: Vec<State> = (0..HEIGHT)
+            .flat_map(|row| (0..WIDTH).map(move |column| (row, column)))
+            .filter(|c| !self.is_cliff(c) && *c != GOAL)
+            .collect();
+        Box::new(states.into_iter())
+    }
+
+    fn action_space(&self, _s: &State) -> Box<dyn Iterator<Item = Action>> {
+        Box::new(Connectivity::Four.moves().iter().copied())
+    }
+
+    fn action_space_len(&self, _s: &State) -> usize {
+        Connectivity::Four.moves().len()
+    }
+
+    fn random_action(&self, _s: &State, rng: &mut dyn RngCore) -> Action {
+        *Connectivity::Four.moves().choose(rng).unwrap()
+    }
+
+    fn random_state(&self, _rng: &mut dyn RngCore) -> State {
+        START
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        *s == GOAL
+    }
+
+    fn step(&self, s: &State, a: &Action) -> (State, f64) {
+        let (d_row, d_column) = a.offset();
+        let row = (s.0 as i64 + d_row).clamp(0, HEIGHT as i64 - 1) as usize;
+        let column = (s.1 as i64 + d_column).clamp(0, WIDTH as i64 - 1) as usize;
+        if self.is_cliff(&(row, column)) {
+            return (START, CLIFF_REWARD);
+        }
+        ((row, column), STEP_REWARD)
+    }
+}
+impl MonteCarloTask<State, Action> for CliffWalking {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, _rng: &mut dyn RngCore) -> (State, f64) {
+        self.step(s, a)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl QLearningTask<State, Action> for CliffWalking {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, _rng: &mut dyn RngCore) -> (State, f64) {
+        self.step(s, a)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl ValueIterationTask<State, Action> for CliffWalking {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn possibilities(&self, s: &State, a: &Action) -> Vec<Possibility<State>> {
+        let (next_state, reward) = self.step(s, a);
+        vec![Possibility {
+            probability: 1.0,
+            next_state,
+            reward,
+        }]
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        self.state_space()
+    }
+
+    fn terminal_state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        Box::new(vec![GOAL].into_iter())
+    }
+}
+
+/// The grid as in Example 6.6, `C` for the cliff and `G` for the goal
+impl Render<State, Action> for CliffWalking {
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        self.render_grid(|s| match policy.get(s) {
+            Some(actions) => arrows(actions),
+            None => ".".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        self.render_grid(|s| match value.get(s) {
+            Some(v) => format!("{:.0}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+impl CliffWalking {
+    fn render_grid(&self, cell: impl Fn(&State) -> String) -> String {
+        render_cells(HEIGHT, WIDTH, |s| {
+            if self.is_cliff(s) {
+                "C".to_string()
+            } else if *s == GOAL {
+                "G".to_string()
+            } else {
+                cell(s)
+            }
+        })
+    }
+}
+
+type State = Cell;
+type Action = Move;
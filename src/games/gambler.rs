@@ -1,7 +1,10 @@
-use rand::Rng;
+use std::collections::HashMap;
+
+use rand::{Rng, RngCore};
 
 use crate::{
     monte_carlo::MonteCarloTask,
+    render::{sparkline, Render},
     value_iteration::{Possibility, ValueIterationTask},
 };
 
@@ -31,16 +34,16 @@ impl MonteCarloTask<State, Action> for Gambler {
         (*s + 1) as usize
     }
 
-    fn random_action(&self, s: &State) -> Action {
-        rand::thread_rng().gen_range(0..*s + 1)
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        rng.gen_range(0..*s + 1)
     }
 
-    fn random_state(&self) -> State {
-        rand::thread_rng().gen_range(1..99 + 1)
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        rng.gen_range(1..99 + 1)
     }
 
-    fn transit(&self, s: &State, a: &Action) -> (State, f64) {
-        let rnd = rand::thread_rng().gen_range(0.0..1.0);
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        let rnd = rng.gen_range(0.0..1.0);
         if rnd < HEAD_PROBABILITY {
             let s_ = i32::min(s + a, 100);
             let r = if s_ == 100 { 1.0 } else { 0.0 };
@@ -104,5 +107,43 @@ impl ValueIterationTask<State, Action> for Gambler {
     }
 }
 
+/// Sparklines over capital 1 to 99
+impl Render<State, Action> for Gambler {
+    /// The smallest of tied stakes
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        let stakes: Vec<Option<f64>> = self
+            .state_space()
+            .map(|s| {
+                policy
+                    .get(&s)
+                    .and_then(|a| a.iter().min())
+                    .map(|a| *a as f64)
+            })
+            .collect();
+        let max = stakes.iter().flatten().fold(0.0, |max: f64, a| max.max(*a));
+        format!(
+            "stake by capital 1..99, up to {}\n{}\n",
+            max,
+            sparkline(&stakes)
+        )
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        let values: Vec<Option<f64>> = self.state_space().map(|s| value.get(&s).copied()).collect();
+        let (min, max) = values
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        format!(
+            "V by capital 1..99, from {:.3} to {:.3}\n{}\n",
+            min,
+            max,
+            sparkline(&values)
+        )
+    }
+}
+
 type State = i32;
 type Action = i32;
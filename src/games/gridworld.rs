@@ -0,0 +1,440 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    monte_carlo::MonteCarloTask,
+    q_learning::QLearningTask,
+    render::{table, Render},
+    value_iteration::{Possibility, ValueIterationTask},
+};
+
+/// A rectangular grid world
+///
+/// Moving off the grid or into a wall leaves the agent in place. Every move earns `step_reward`,
+/// plus `bump_reward` if it was blocked or the reward of the cell entered otherwise.
+/// With probability `slip` the chosen move is replaced by a uniformly random one.
+///
+/// Without terminal cells the task is continuing; sample-based learners need `with_max_steps`.
+#[derive(Debug, Clone)]
+pub struct Gridworld {
+    height: usize,
+    width: usize,
+    walls: HashSet<Cell>,
+    terminals: HashSet<Cell>,
+    rewards: HashMap<Cell, f64>,
+    /// Any move from the key cell lands on the target cell with the reward
+    teleports: HashMap<Cell, (Cell, f64)>,
+    starts: Vec<Cell>,
+    step_reward: f64,
+    bump_reward: f64,
+    slip: f64,
+    connectivity: Connectivity,
+    gamma: f64,
+}
+
+impl Gridworld {
+    /// An open, undiscounted, deterministic, 4-connected grid with no rewards
+    pub fn new(height: usize, width: usize) -> Self {
+        Self {
+            height,
+            width,
+            walls: HashSet::new(),
+            terminals: HashSet::new(),
+            rewards: HashMap::new(),
+            teleports: HashMap::new(),
+            starts: vec![],
+            step_reward: 0.0,
+            bump_reward: 0.0,
+            slip: 0.0,
+            connectivity: Connectivity::Four,
+            gamma: 1.0,
+        }
+    }
+
+    /// Example 4.1: $4 \times 4$, terminal corners, $-1$ per move, undiscounted
+    pub fn example_4_1() -> Self {
+        Self::new(4, 4)
+            .with_terminals([(0, 0), (3, 3)])
+            .with_step_reward(-1.0)
+    }
+
+    /// Example 3.5: $5 \times 5$, continuing, $-1$ for bumping into the edge, $\gamma = 0.9$
+    ///
+    /// Every move from A goes to A' for $+10$, and from B to B' for $+5$.
+    pub fn example_3_5() -> Self {
+        Self::new(5, 5)
+            .with_teleport((0, 1), (4, 1), 10.0)
+            .with_teleport((0, 3), (2, 3), 5.0)
+            .with_bump_reward(-1.0)
+            .with_gamma(0.9)
+    }
+
+    pub fn with_walls(mut self, walls: impl IntoIterator<Item = Cell>) -> Self {
+        self.walls.extend(walls);
+        self
+    }
+
+    pub fn with_terminals(mut self, terminals: impl IntoIterator<Item = Cell>) -> Self {
+        self.terminals.extend(terminals);
+        self
+    }
+
+    /// Reward for entering `cell`, on top of `step_reward`
+    pub fn with_reward(mut self, cell: Cell, reward: f64) -> Self {
+        self.rewards.insert(cell, reward);
+        self
+    }
+
+    pub fn with_teleport(mut self, from: Cell, to: Cell, reward: f64) -> Self {
+        self.teleports.insert(from, (to, reward));
+        self
+    }
+
+    /// Start episodes uniformly from `starts` instead of from any non-terminal cell
+    pub fn with_starts(mut self, starts: impl IntoIterator<Item = Cell>) -> Self {
+        self.starts = starts.into_iter().collect();
+        self
+    }
+
+    pub fn with_step_reward(mut self, step_reward: f64) -> Self {
+        self.step_reward = step_reward;
+        self
+    }
+
+    pub fn with_bump_reward(mut self, bump_reward: f64) -> Self {
+        self.bump_reward = bump_reward;
+        self
+    }
+
+    /// Panics unless `slip` is a probability
+    pub fn with_slip(mut self, slip: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&slip),
+            "slip must be in [0, 1], not {}",
+            slip
+        );
+        self.slip = slip;
+        self
+    }
+
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self
+    }
+
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn is_wall(&self, cell: &Cell) -> bool {
+        self.walls.contains(cell)
+    }
+
+    /// Non-wall, non-terminal cells, row by row
+    pub fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        let states: Vec<State> = (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |column| (row, column)))
+            .filter(|c| !self.walls.contains(c) && !self.terminals.contains(c))
+            .collect();
+        Box::new(states.into_iter())
+    }
+
+    fn action_space(&self, _s: &State) -> Box<dyn Iterator<Item = Action>> {
+        Box::new(self.connectivity.moves().iter().copied())
+    }
+
+    fn action_space_len(&self, _s: &State) -> usize {
+        self.connectivity.moves().len()
+    }
+
+    fn random_action(&self, _s: &State, rng: &mut dyn RngCore) -> Action {
+        *self.connectivity.moves().choose(rng).unwrap()
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        if let Some(s) = self.starts.choose(rng) {
+            return *s;
+        }
+        let states: Vec<State> = self.state_space().collect();
+        *states.choose(rng).unwrap()
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        let a = if rng.gen_bool(self.slip) {
+            self.random_action(s, rng)
+        } else {
+            *a
+        };
+        self.step(s, &a)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.terminals.contains(s)
+    }
+
+    /// The deterministic outcome of taking move `a` in `s`
+    fn step(&self, s: &State, a: &Action) -> (State, f64) {
+        if let Some((to, reward)) = self.teleports.get(s) {
+            return (*to, *reward);
+        }
+        let (d_row, d_column) = a.offset();
+        let row = s.0 as i64 + d_row;
+        let column = s.1 as i64 + d_column;
+        let inside =
+            (0..self.height as i64).contains(&row) && (0..self.width as i64).contains(&column);
+        let next = (row as usize, column as usize);
+        if !inside || self.walls.contains(&next) {
+            return (*s, self.step_reward + self.bump_reward);
+        }
+        let reward = self.rewards.get(&next).unwrap_or(&0.0);
+        (next, self.step_reward + reward)
+    }
+}
+impl MonteCarloTask<State, Action> for Gridworld {
+    fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl QLearningTask<State, Action> for Gridworld {
+    fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl ValueIterationTask<State, Action> for Gridworld {
+    fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    fn possibilities(&self, s: &State, a: &Action) -> Vec<Possibility<State>> {
+        let moves = self.connectivity.moves();
+        let mut possibilities = vec![];
+        let (next_state, reward) = self.step(s, a);
+        possibilities.push(Possibility {
+            probability: 1.0 - self.slip,
+            next_state,
+            reward,
+        });
+        if self.slip > 0.0 {
+            for slipped in moves {
+                let (next_state, reward) = self.step(s, slipped);
+                possibilities.push(Possibility {
+                    probability: self.slip / moves.len() as f64,
+                    next_state,
+                    reward,
+                });
+            }
+        }
+        possibilities
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        self.state_space()
+    }
+
+    fn terminal_state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        let terminals: Vec<State> = self.terminals.iter().copied().collect();
+        Box::new(terminals.into_iter())
+    }
+}
+
+/// The grid as laid out, `#` for walls and `*` for terminal cells
+impl Render<State, Action> for Gridworld {
+    /// Arrows of the greedy moves, all of them where several tie
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        self.render_grid(|s| match policy.get(s) {
+            Some(actions) => arrows(actions),
+            None => ".".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        self.render_grid(|s| match value.get(s) {
+            Some(v) => format!("{:.1}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+impl Gridworld {
+    fn render_grid(&self, cell: impl Fn(&State) -> String) -> String {
+        render_cells(self.height, self.width, |s| {
+            if self.walls.contains(s) {
+                "#".to_string()
+            } else if self.terminals.contains(s) {
+                "*".to_string()
+            } else {
+                cell(s)
+            }
+        })
+    }
+}
+
+/// A table of `height` rows and `width` columns labelled by their indices
+pub fn render_cells(height: usize, width: usize, cell: impl Fn(&Cell) -> String) -> String {
+    let cells: Vec<Vec<String>> = (0..height)
+        .map(|row| (0..width).map(|column| cell(&(row, column))).collect())
+        .collect();
+    table(
+        &(0..width).map(|c| c.to_string()).collect::<Vec<_>>(),
+        &(0..height).map(|r| r.to_string()).collect::<Vec<_>>(),
+        &cells,
+    )
+}
+
+/// The arrows of all tied `moves`, or `.` if there are none
+pub fn arrows(moves: &[Move]) -> String {
+    if moves.is_empty() {
+        return ".".to_string();
+    }
+    moves.iter().map(Move::arrow).collect()
+}
+
+/// Moves available in every cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectivity {
+    /// Up, down, left and right
+    Four,
+    /// Also the diagonals, like a king in chess
+    Eight,
+    /// King's moves and staying put
+    Nine,
+}
+impl Connectivity {
+    pub fn moves(&self) -> &'static [Move] {
+        match self {
+            Connectivity::Four => &[Move::Up, Move::Down, Move::Left, Move::Right],
+            Connectivity::Eight => &[
+                Move::Up,
+                Move::Down,
+                Move::Left,
+                Move::Right,
+                Move::UpLeft,
+                Move::UpRight,
+                Move::DownLeft,
+                Move::DownRight,
+            ],
+            Connectivity::Nine => &[
+                Move::Up,
+                Move::Down,
+                Move::Left,
+                Move::Right,
+                Move::UpLeft,
+                Move::UpRight,
+                Move::DownLeft,
+                Move::DownRight,
+                Move::Stay,
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+    Stay,
+}
+impl Move {
+    /// $(\Delta \text{row}, \Delta \text{column})$, rows counting down from the top
+    pub fn offset(&self) -> (i64, i64) {
+        match self {
+            Move::Up => (-1, 0),
+            Move::Down => (1, 0),
+            Move::Left => (0, -1),
+            Move::Right => (0, 1),
+            Move::UpLeft => (-1, -1),
+            Move::UpRight => (-1, 1),
+            Move::DownLeft => (1, -1),
+            Move::DownRight => (1, 1),
+            Move::Stay => (0, 0),
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Move::Up => '↑',
+            Move::Down => '↓',
+            Move::Left => '←',
+            Move::Right => '→',
+            Move::UpLeft => '↖',
+            Move::UpRight => '↗',
+            Move::DownLeft => '↙',
+            Move::DownRight => '↘',
+            Move::Stay => '·',
+        }
+    }
+}
+
+/// (row, column), from the top left
+pub type Cell = (usize, usize);
+type State = Cell;
+type Action = Move;
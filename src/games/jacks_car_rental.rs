@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::value_iteration::{Possibility, ValueIterationTask};
+use crate::{
+    render::{table, Render},
+    value_iteration::{Possibility, ValueIterationTask},
+};
 
 const GAMMA: f64 = 0.9;
 
@@ -67,6 +70,39 @@ impl ValueIterationTask<State, Action> for JacksCarRental {
     }
 }
 
+/// Rows of cars at the first location from 20 down, columns of cars at the second location
+impl Render<State, Action> for JacksCarRental {
+    /// Cars moved from the first location to the second; `?` where several tie
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        render_table(|s| match policy.get(s).map(Vec::as_slice) {
+            Some([a]) => a.to_string(),
+            Some([]) | None => ".".to_string(),
+            Some(_) => "?".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        render_table(|s| match value.get(s) {
+            Some(v) => format!("{:.0}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+
+fn render_table(cell: impl Fn(&State) -> String) -> String {
+    let rows: Vec<u32> = (0..=20).rev().collect();
+    let columns: Vec<u32> = (0..=20).collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|s0| columns.iter().map(|s1| cell(&(*s0, *s1))).collect())
+        .collect();
+    table(
+        &columns.iter().map(u32::to_string).collect::<Vec<_>>(),
+        &rows.iter().map(u32::to_string).collect::<Vec<_>>(),
+        &cells,
+    )
+}
+
 type State = (u32, u32);
 type Action = i32;
 
@@ -0,0 +1,184 @@
+use std::{cell, collections::HashMap};
+
+use rand::{seq::SliceRandom, RngCore};
+
+use crate::{
+    games::gridworld::{arrows, render_cells, Cell, Connectivity, Move},
+    q_learning::QLearningTask,
+    render::Render,
+};
+
+const GAMMA: f64 = 0.95;
+const GOAL_REWARD: f64 = 1.0;
+
+/// A maze from chapter 8, where only reaching the goal pays $+1$
+///
+/// The maze counts every step taken in it, across episodes. Once `change_after` steps have been
+/// taken, the walls are swapped for the changed ones, which makes the task non-stationary. An
+/// agent whose cell turns into a wall is put back on the start.
+#[derive(Debug, Clone)]
+pub struct Maze {
+    height: usize,
+    width: usize,
+    start: Cell,
+    goal: Cell,
+    walls: Vec<Cell>,
+    change: Option<(usize, Vec<Cell>)>,
+    steps: cell::Cell<usize>,
+}
+
+impl Maze {
+    pub fn new(height: usize, width: usize, start: Cell, goal: Cell, walls: Vec<Cell>) -> Self {
+        Self {
+            height,
+            width,
+            start,
+            goal,
+            walls,
+            change: None,
+            steps: cell::Cell::new(0),
+        }
+    }
+
+    /// Replace the walls with `walls` from step `change_after` on
+    pub fn with_change(mut self, change_after: usize, walls: Vec<Cell>) -> Self {
+        self.change = Some((change_after, walls));
+        self
+    }
+
+    /// Figure 8.2
+    pub fn dyna() -> Self {
+        Self::new(
+            6,
+            9,
+            (2, 0),
+            (0, 8),
+            vec![(1, 2), (2, 2), (3, 2), (4, 5), (0, 7), (1, 7), (2, 7)],
+        )
+    }
+
+    /// Figure 8.4: the gap on the right of the barrier moves to its left after `change_after` steps
+    pub fn blocking(change_after: usize) -> Self {
+        Self::new(6, 9, (5, 3), (0, 8), (0..8).map(|c| (3, c)).collect())
+            .with_change(change_after, (1..9).map(|c| (3, c)).collect())
+    }
+
+    /// Figure 8.5: a second gap opens on the right of the barrier after `change_after` steps
+    pub fn shortcut(change_after: usize) -> Self {
+        Self::new(6, 9, (5, 3), (0, 8), (1..9).map(|c| (3, c)).collect())
+            .with_change(change_after, (1..8).map(|c| (3, c)).collect())
+    }
+
+    /// Steps taken so far, over all episodes
+    pub fn steps(&self) -> usize {
+        self.steps.get()
+    }
+
+    /// Start counting steps over, with the original walls
+    pub fn reset(&self) {
+        self.steps.set(0);
+    }
+
+    /// The walls as of now
+    pub fn walls(&self) -> &[Cell] {
+        match &self.change {
+            Some((change_after, walls)) if *change_after <= self.steps.get() => walls,
+            _ => &self.walls,
+        }
+    }
+
+    pub fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        let walls = self.walls();
+        let states: Vec<State> = (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |column| (row, column)))
+            .filter(|c| !walls.contains(c) && *c != self.goal)
+            .collect();
+        Box::new(states.into_iter())
+    }
+}
+impl QLearningTask<State, Action> for Maze {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, _s: &State) -> Box<dyn Iterator<Item = Action>> {
+        Box::new(Connectivity::Four.moves().iter().copied())
+    }
+
+    fn action_space_len(&self, _s: &State) -> usize {
+        Connectivity::Four.moves().len()
+    }
+
+    fn random_action(&self, _s: &State, rng: &mut dyn RngCore) -> Action {
+        *Connectivity::Four.moves().choose(rng).unwrap()
+    }
+
+    fn random_state(&self, _rng: &mut dyn RngCore) -> State {
+        self.start
+    }
+
+    fn transit(&self, s: &State, a: &Action, _rng: &mut dyn RngCore) -> (State, f64) {
+        let (d_row, d_column) = a.offset();
+        let row = s.0 as i64 + d_row;
+        let column = s.1 as i64 + d_column;
+        let inside =
+            (0..self.height as i64).contains(&row) && (0..self.width as i64).contains(&column);
+        let next = (row as usize, column as usize);
+        let s_next = if inside && !self.walls().contains(&next) {
+            next
+        } else {
+            *s
+        };
+        self.steps.set(self.steps.get() + 1);
+        // the walls may have just changed, and moved onto the agent
+        let s_next = if self.walls().contains(&s_next) {
+            self.start
+        } else {
+            s_next
+        };
+        let r = if s_next == self.goal {
+            GOAL_REWARD
+        } else {
+            0.0
+        };
+        (s_next, r)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        *s == self.goal
+    }
+}
+
+/// The maze with its current walls, `#` for walls and `G` for the goal
+impl Render<State, Action> for Maze {
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        self.render_grid(|s| match policy.get(s) {
+            Some(actions) => arrows(actions),
+            None => ".".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        self.render_grid(|s| match value.get(s) {
+            Some(v) => format!("{:.2}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+impl Maze {
+    fn render_grid(&self, cell: impl Fn(&State) -> String) -> String {
+        let walls = self.walls();
+        render_cells(self.height, self.width, |s| {
+            if walls.contains(s) {
+                "#".to_string()
+            } else if *s == self.goal {
+                "G".to_string()
+            } else {
+                cell(s)
+            }
+        })
+    }
+}
+
+type State = Cell;
+type Action = Move;
@@ -1,3 +1,9 @@
 pub mod blackjack;
+pub mod cliff_walking;
 pub mod gambler;
+pub mod gridworld;
 pub mod jacks_car_rental;
+pub mod maze;
+pub mod racetrack;
+pub mod random_walk;
+pub mod windy_gridworld;
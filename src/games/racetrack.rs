@@ -0,0 +1,305 @@
+use std::{collections::HashMap, io, path::Path};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{games::gridworld::Cell, monte_carlo::MonteCarloTask, q_learning::QLearningTask};
+
+const GAMMA: f64 = 1.0;
+const MAX_SPEED: i32 = 4;
+const NOISE: f64 = 0.1;
+const STEP_REWARD: f64 = -1.0;
+
+/// Exercise 5.12: drive from the start line to the finish line as fast as possible
+///
+/// Each step adds $-1$, $0$ or $+1$ to both velocity components, which stay in $0..=4$ and may
+/// only both be zero on the start line. With probability `noise` both increments are zero
+/// instead. A path crossing the finish line ends the episode; one leaving the track anywhere
+/// else sends the car back to a random start cell at rest.
+#[derive(Debug, Clone)]
+pub struct Racetrack {
+    track: Vec<Vec<Surface>>,
+    starts: Vec<Cell>,
+    noise: f64,
+}
+
+impl Racetrack {
+    /// Read a layout, one row per line from the top: `#` off the track, `.` on it, `S` the
+    /// start line and `F` the finish line
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(layout: &str) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let track = layout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c {
+                        '#' => Ok(Surface::Off),
+                        '.' => Ok(Surface::Track),
+                        'S' => Ok(Surface::Start),
+                        'F' => Ok(Surface::Finish),
+                        _ => Err(invalid(&format!("unknown track cell {:?}", c))),
+                    })
+                    .collect::<io::Result<Vec<Surface>>>()
+            })
+            .collect::<io::Result<Vec<Vec<Surface>>>>()?;
+        let width = track.first().map(Vec::len).unwrap_or(0);
+        if track.iter().any(|row| row.len() != width) {
+            return Err(invalid("rows of different lengths"));
+        }
+        let cells = |surface: Surface| -> Vec<Cell> {
+            (0..track.len())
+                .flat_map(|row| (0..width).map(move |column| (row, column)))
+                .filter(|(row, column)| track[*row][*column] == surface)
+                .collect()
+        };
+        let starts = cells(Surface::Start);
+        if starts.is_empty() || cells(Surface::Finish).is_empty() {
+            return Err(invalid("no start or finish line"));
+        }
+        Ok(Self {
+            track,
+            starts,
+            noise: NOISE,
+        })
+    }
+
+    /// Probability of both velocity increments being zero regardless of the action
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    pub fn starts(&self) -> &[Cell] {
+        &self.starts
+    }
+
+    /// At rest on the start line at `cell`
+    pub fn start_state(cell: Cell) -> State {
+        State {
+            row: cell.0,
+            column: cell.1,
+            up: 0,
+            right: 0,
+        }
+    }
+
+    fn surface(&self, row: i64, column: i64) -> Surface {
+        if row < 0 || column < 0 {
+            return Surface::Off;
+        }
+        *self
+            .track
+            .get(row as usize)
+            .and_then(|r| r.get(column as usize))
+            .unwrap_or(&Surface::Off)
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        let s = *s;
+        let actions: Vec<Action> = (-1..=1)
+            .flat_map(|d_up| (-1..=1).map(move |d_right| (d_up, d_right)))
+            .filter(|(d_up, d_right)| {
+                let up = s.up + d_up;
+                let right = s.right + d_right;
+                (0..=MAX_SPEED).contains(&up)
+                    && (0..=MAX_SPEED).contains(&right)
+                    && (up, right) != (0, 0)
+            })
+            .collect();
+        Box::new(actions.into_iter())
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space(s).count()
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        let actions: Vec<Action> = self.action_space(s).collect();
+        *actions.choose(rng).unwrap()
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        Self::start_state(*self.starts.choose(rng).unwrap())
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        let increment = if rng.gen_bool(self.noise) { (0, 0) } else { *a };
+        match self.drive(s, increment) {
+            Some(s_next) => (s_next, STEP_REWARD),
+            None => (self.random_state(rng), STEP_REWARD),
+        }
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.is_finish(s)
+    }
+
+    pub fn is_finish(&self, s: &State) -> bool {
+        self.track[s.row][s.column] == Surface::Finish
+    }
+
+    /// Where the car ends up after changing its velocity by `increment`; `None` if it crashes
+    ///
+    /// The path is checked cell by cell, so a car that reaches the finish line before it would
+    /// leave the track finishes.
+    fn drive(&self, s: &State, increment: Action) -> Option<State> {
+        let up = s.up + increment.0;
+        let right = s.right + increment.1;
+        let substeps = 4 * up.max(right).max(1);
+        for k in 1..=substeps {
+            let fraction = k as f64 / substeps as f64;
+            let row = (s.row as f64 - up as f64 * fraction).round() as i64;
+            let column = (s.column as f64 + right as f64 * fraction).round() as i64;
+            match self.surface(row, column) {
+                Surface::Off => return None,
+                Surface::Finish => {
+                    return Some(State {
+                        row: row as usize,
+                        column: column as usize,
+                        up,
+                        right,
+                    })
+                }
+                Surface::Track | Surface::Start => (),
+            }
+        }
+        Some(State {
+            row: (s.row as i64 - up as i64) as usize,
+            column: s.column + right as usize,
+            up,
+            right,
+        })
+    }
+
+    /// Follow the first of the greedy actions of `policy` from `start` without noise
+    ///
+    /// Ends at the finish line, at a crash, at a state `policy` has no action for, or after
+    /// `max_steps` steps.
+    pub fn trajectory(
+        &self,
+        policy: &HashMap<State, Vec<Action>>,
+        start: State,
+        max_steps: usize,
+    ) -> Vec<State> {
+        let mut trajectory = vec![start];
+        let mut s = start;
+        while trajectory.len() <= max_steps && !self.in_terminal_state_space(&s) {
+            let Some(a) = policy.get(&s).and_then(|actions| actions.first()) else {
+                break;
+            };
+            let Some(s_next) = self.drive(&s, *a) else {
+                break;
+            };
+            trajectory.push(s_next);
+            s = s_next;
+        }
+        trajectory
+    }
+
+    /// The track with the positions of `trajectory` numbered by step, modulo 10
+    pub fn render_trajectory(&self, trajectory: &[State]) -> String {
+        let mut grid: Vec<Vec<char>> = self
+            .track
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|surface| match surface {
+                        Surface::Off => '#',
+                        Surface::Track => '.',
+                        Surface::Start => 'S',
+                        Surface::Finish => 'F',
+                    })
+                    .collect()
+            })
+            .collect();
+        for (t, s) in trajectory.iter().enumerate() {
+            grid[s.row][s.column] = char::from_digit((t % 10) as u32, 10).unwrap();
+        }
+        grid.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
+impl MonteCarloTask<State, Action> for Racetrack {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl QLearningTask<State, Action> for Racetrack {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Surface {
+    Off,
+    Track,
+    Start,
+    Finish,
+}
+
+/// Position from the top left of the layout and velocity towards the finish line
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct State {
+    pub row: usize,
+    pub column: usize,
+    pub up: i32,
+    pub right: i32,
+}
+
+/// Increments of the (up, right) velocity
+pub type Action = (i32, i32);
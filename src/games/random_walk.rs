@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use rand::{Rng, RngCore};
+
+use crate::prediction::PredictionTask;
+
+const GAMMA: f64 = 1.0;
+
+/// States $1, \dots, n$ in a row between terminal states $0$ and $n + 1$, starting in the middle
+///
+/// Every step goes left or right with equal probability. Exiting on the right earns $+1$ and on
+/// the left `left_reward`; all other rewards are zero.
+pub struct RandomWalk {
+    n: i32,
+    left_reward: f64,
+}
+
+impl RandomWalk {
+    pub fn new(n: i32, left_reward: f64) -> Self {
+        Self { n, left_reward }
+    }
+
+    /// Example 6.2: states A to E as 1 to 5
+    pub fn five_states() -> Self {
+        Self::new(5, 0.0)
+    }
+
+    /// Example 7.1, with $-1$ on the left
+    pub fn nineteen_states() -> Self {
+        Self::new(19, -1.0)
+    }
+
+    pub fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        Box::new(1..self.n + 1)
+    }
+
+    /// $v(s)$, from the probability $s / (n + 1)$ of exiting on the right
+    pub fn true_value(&self) -> HashMap<State, f64> {
+        self.state_space()
+            .map(|s| {
+                let right = s as f64 / (self.n + 1) as f64;
+                (s, right + (1.0 - right) * self.left_reward)
+            })
+            .collect()
+    }
+}
+impl PredictionTask<State> for RandomWalk {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn random_state(&self, _rng: &mut dyn RngCore) -> State {
+        (self.n + 1) / 2
+    }
+
+    fn transit(&self, s: &State, rng: &mut dyn RngCore) -> (State, f64) {
+        let s_next = if rng.gen_bool(0.5) { s + 1 } else { s - 1 };
+        let r = if s_next == self.n + 1 {
+            1.0
+        } else if s_next == 0 {
+            self.left_reward
+        } else {
+            0.0
+        };
+        (s_next, r)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        *s == 0 || *s == self.n + 1
+    }
+}
+
+type State = i32;
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, RngCore};
+
+use crate::{
+    games::gridworld::{arrows, Cell, Connectivity, Move},
+    monte_carlo::MonteCarloTask,
+    q_learning::QLearningTask,
+    render::{table, Render},
+    value_iteration::{Possibility, ValueIterationTask},
+};
+
+const GAMMA: f64 = 1.0;
+const HEIGHT: usize = 7;
+const WIDTH: usize = 10;
+const START: Cell = (3, 0);
+const GOAL: Cell = (3, 7);
+const STEP_REWARD: f64 = -1.0;
+/// Upward push in each column
+const WIND: [i64; WIDTH] = [0, 0, 0, 1, 1, 1, 2, 2, 1, 0];
+
+/// Example 6.5: $-1$ per move until the goal, with the wind of the column left pushing upwards
+///
+/// Exercise 6.9 adds King's moves, with or without staying put, and Exercise 6.10 makes the wind
+/// of windy columns vary by one either way with probability $1/3$ each.
+pub struct WindyGridworld {
+    connectivity: Connectivity,
+    stochastic_wind: bool,
+}
+
+impl Default for WindyGridworld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindyGridworld {
+    /// 4-connected moves and steady wind, as in Example 6.5
+    pub fn new() -> Self {
+        Self {
+            connectivity: Connectivity::Four,
+            stochastic_wind: false,
+        }
+    }
+
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self
+    }
+
+    pub fn with_stochastic_wind(mut self) -> Self {
+        self.stochastic_wind = true;
+        self
+    }
+
+    pub fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        let states: Vec<State> = (0..HEIGHT)
+            .flat_map(|row| (0..WIDTH).map(move |column| (row, column)))
+            .filter(|c| *c != GOAL)
+            .collect();
+        Box::new(states.into_iter())
+    }
+
+    fn action_space(&self, _s: &State) -> Box<dyn Iterator<Item = Action>> {
+        Box::new(self.connectivity.moves().iter().copied())
+    }
+
+    fn action_space_len(&self, _s: &State) -> usize {
+        self.connectivity.moves().len()
+    }
+
+    fn random_action(&self, _s: &State, rng: &mut dyn RngCore) -> Action {
+        *self.connectivity.moves().choose(rng).unwrap()
+    }
+
+    fn random_state(&self, _rng: &mut dyn RngCore) -> State {
+        START
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        let wind = self.winds(s);
+        let wind = *wind.choose(rng).unwrap();
+        (self.step(s, a, wind), STEP_REWARD)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        *s == GOAL
+    }
+
+    /// Equally likely wind strengths in the column of `s`
+    fn winds(&self, s: &State) -> Vec<i64> {
+        let wind = WIND[s.1];
+        if self.stochastic_wind && 0 < wind {
+            vec![wind - 1, wind, wind + 1]
+        } else {
+            vec![wind]
+        }
+    }
+
+    fn step(&self, s: &State, a: &Action, wind: i64) -> State {
+        let (d_row, d_column) = a.offset();
+        let row = (s.0 as i64 + d_row - wind).clamp(0, HEIGHT as i64 - 1);
+        let column = (s.1 as i64 + d_column).clamp(0, WIDTH as i64 - 1);
+        (row as usize, column as usize)
+    }
+}
+impl MonteCarloTask<State, Action> for WindyGridworld {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl QLearningTask<State, Action> for WindyGridworld {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn action_space_len(&self, s: &State) -> usize {
+        self.action_space_len(s)
+    }
+
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action {
+        self.random_action(s, rng)
+    }
+
+    fn random_state(&self, rng: &mut dyn RngCore) -> State {
+        self.random_state(rng)
+    }
+
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64) {
+        self.transit(s, a, rng)
+    }
+
+    fn in_terminal_state_space(&self, s: &State) -> bool {
+        self.in_terminal_state_space(s)
+    }
+}
+impl ValueIterationTask<State, Action> for WindyGridworld {
+    fn gamma(&self) -> f64 {
+        GAMMA
+    }
+
+    fn possibilities(&self, s: &State, a: &Action) -> Vec<Possibility<State>> {
+        let winds = self.winds(s);
+        let probability = 1.0 / winds.len() as f64;
+        winds
+            .into_iter()
+            .map(|wind| Possibility {
+                probability,
+                next_state: self.step(s, a, wind),
+                reward: STEP_REWARD,
+            })
+            .collect()
+    }
+
+    fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>> {
+        self.action_space(s)
+    }
+
+    fn state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        self.state_space()
+    }
+
+    fn terminal_state_space(&self) -> Box<dyn Iterator<Item = State>> {
+        Box::new(vec![GOAL].into_iter())
+    }
+}
+
+/// The grid as in Example 6.5, `G` for the goal, with the wind of each column underneath
+impl Render<State, Action> for WindyGridworld {
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String {
+        render_grid(|s| match policy.get(s) {
+            Some(actions) => arrows(actions),
+            None => ".".to_string(),
+        })
+    }
+
+    fn render_values(&self, value: &HashMap<State, f64>) -> String {
+        render_grid(|s| match value.get(s) {
+            Some(v) => format!("{:.0}", v),
+            None => ".".to_string(),
+        })
+    }
+}
+
+fn render_grid(cell: impl Fn(&State) -> String) -> String {
+    let mut cells: Vec<Vec<String>> = (0..HEIGHT)
+        .map(|row| {
+            (0..WIDTH)
+                .map(|column| match (row, column) {
+                    GOAL => "G".to_string(),
+                    s => cell(&s),
+                })
+                .collect()
+        })
+        .collect();
+    cells.push(WIND.iter().map(i64::to_string).collect());
+    let mut row_labels: Vec<String> = (0..HEIGHT).map(|r| r.to_string()).collect();
+    row_labels.push("^".to_string());
+    table(
+        &(0..WIDTH).map(|c| c.to_string()).collect::<Vec<_>>(),
+        &row_labels,
+        &cells,
+    )
+}
+
+type State = Cell;
+type Action = Move;
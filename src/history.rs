@@ -0,0 +1,40 @@
+use std::ops::ControlFlow;
+
+use serde::{Deserialize, Serialize};
+
+/// What happened in one training episode
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeStats {
+    /// Counting from zero
+    pub episode: usize,
+    /// Discounted return from the start state
+    pub ret: f64,
+    pub length: usize,
+    pub truncated: bool,
+    /// $\epsilon$ or $\tau$ of the behavior policy, if it has one
+    pub exploration: Option<f64>,
+    /// Mean $\alpha$ over the updates of the episode, if the learner has one
+    pub alpha: Option<f64>,
+    /// Max absolute error of the updates of the episode
+    ///
+    /// The TD error for TD learners, the error against the sampled return for Monte Carlo.
+    pub max_abs_td_error: f64,
+}
+
+/// Per-episode statistics of a training run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    pub episodes: Vec<EpisodeStats>,
+}
+impl History {
+    pub fn returns(&self) -> Vec<f64> {
+        self.episodes.iter().map(|e| e.ret).collect()
+    }
+
+    pub fn num_truncated(&self) -> usize {
+        self.episodes.iter().filter(|e| e.truncated).count()
+    }
+}
+
+/// Called after each episode; returning [`ControlFlow::Break`] stops training early
+pub type Observer<'a> = &'a mut dyn FnMut(&EpisodeStats) -> ControlFlow<()>;
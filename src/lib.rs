@@ -1,11 +1,26 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+use ties::Ties;
+
+pub mod bandits;
+pub mod evaluation;
+pub mod exploration;
 pub mod games;
+pub mod history;
 pub mod monte_carlo;
+pub mod plot;
+pub mod policy;
+pub mod prediction;
 pub mod q_learning;
+pub mod render;
+pub mod schedule;
+pub mod serialization;
+pub mod sweep;
+pub mod ties;
 pub mod value_iteration;
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
 pub struct StateActionPair<State, Action>
 where
     State: Copy + std::hash::Hash + std::cmp::Eq,
@@ -19,27 +34,19 @@ pub fn max_value_by_actions<State, Action>(
     value: &HashMap<StateActionPair<State, Action>, f64>,
     s: &State,
     action_space: impl Iterator<Item = Action>,
+    ties: &Ties,
 ) -> (f64, Vec<Action>)
 where
     State: Copy + std::hash::Hash + std::cmp::Eq,
-    Action: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord,
 {
-    let mut max_v = f64::MIN;
-    let mut max_a = vec![];
-    for a in action_space {
+    ties.max(action_space.map(|a| {
         let v = *value
             .get(&StateActionPair {
                 state: *s,
                 action: a,
             })
             .unwrap_or(&0.0);
-        if max_v < v {
-            max_a = vec![a];
-        }
-        if max_v == v {
-            max_a.push(a);
-        }
-        max_v = f64::max(max_v, v);
-    }
-    (max_v, max_a)
+        (a, v)
+    }))
 }
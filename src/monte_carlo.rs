@@ -1,50 +1,163 @@
 use std::collections::HashMap;
 
-use rand::{seq::SliceRandom, Rng};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::{max_value_by_actions, StateActionPair};
+use crate::{
+    exploration::Exploration,
+    history::{EpisodeStats, History, Observer},
+    max_value_by_actions,
+    policy::Policy,
+    serialization::map_as_pairs,
+    ties::Ties,
+    StateActionPair,
+};
 
 pub trait MonteCarloTask<State, Action> {
     fn gamma(&self) -> f64;
     fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>>;
     fn action_space_len(&self, s: &State) -> usize;
-    fn random_action(&self, s: &State) -> Action;
-    fn random_state(&self) -> State;
-    fn transit(&self, s: &State, a: &Action) -> (State, f64);
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action;
+    fn random_state(&self, rng: &mut dyn RngCore) -> State;
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64);
     fn in_terminal_state_space(&self, s: &State) -> bool;
 }
 
+/// Everything an off-policy Monte Carlo control run needs to pick up where it left off
+///
+/// Training in chunks on the same state is equivalent to training in one go.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "State: Serialize, Action: Serialize",
+    deserialize = "State: Deserialize<'de>, Action: Deserialize<'de>"
+))]
+pub struct MonteCarloState<State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    /// $Q$
+    #[serde(with = "map_as_pairs")]
+    pub value: HashMap<StateActionPair<State, Action>, f64>,
+    /// $C$
+    #[serde(with = "map_as_pairs")]
+    pub l_importance_sum: HashMap<StateActionPair<State, Action>, f64>,
+    /// $\pi$
+    #[serde(with = "map_as_pairs")]
+    pub policy: HashMap<State, Vec<Action>>,
+    /// $N(s, a)$
+    #[serde(with = "map_as_pairs")]
+    pub visits: HashMap<StateActionPair<State, Action>, usize>,
+    /// Number of episodes run so far, which drives the schedules
+    pub episode: usize,
+    pub rng: ChaCha8Rng,
+}
+impl<State, Action> MonteCarloState<State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub fn new(seed: u64) -> Self {
+        Self {
+            value: HashMap::new(),
+            l_importance_sum: HashMap::new(),
+            policy: HashMap::new(),
+            visits: HashMap::new(),
+            episode: 0,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
 pub struct MonteCarlo<State, Action> {
     task: Box<dyn MonteCarloTask<State, Action>>,
+    ties: Ties,
+    max_steps: Option<usize>,
 }
 
 impl<State, Action> MonteCarlo<State, Action>
 where
     State: Copy + std::hash::Hash + std::cmp::Eq,
-    Action: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
 {
     pub fn new(task: Box<dyn MonteCarloTask<State, Action>>) -> Self {
-        Self { task }
+        Self {
+            task,
+            ties: Ties::default(),
+            max_steps: None,
+        }
+    }
+
+    pub fn with_ties(mut self, ties: Ties) -> Self {
+        self.ties = ties;
+        self
+    }
+
+    pub fn task(&self) -> &dyn MonteCarloTask<State, Action> {
+        self.task.as_ref()
+    }
+
+    /// Truncate every episode after `max_steps` steps
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
     }
 
-    /// - `value`: $Q$
-    /// - `weight_sum`: $C$
-    /// - `policy`: $\pi$
-    /// - `prob_explore`: $\epsilon$
+    /// Run `num_episodes` more episodes on `state`
+    ///
+    /// - `exploration`: how the behavior policy $b$ is derived from $Q$
+    /// - `observer`: called after each episode
     pub fn policy_evaluation(
         &self,
-        value: &mut HashMap<StateActionPair<State, Action>, f64>,
-        l_importance_sum: &mut HashMap<StateActionPair<State, Action>, f64>,
-        policy: &mut HashMap<State, Vec<Action>>,
-        prob_explore: f64,
+        state: &mut MonteCarloState<State, Action>,
+        exploration: &Exploration,
         num_episodes: usize,
-    ) {
+        mut observer: Option<Observer>,
+    ) -> History {
+        let MonteCarloState {
+            value,
+            l_importance_sum,
+            policy,
+            visits,
+            episode: episode_count,
+            rng,
+        } = state;
+        let mut history = History::default();
         for _ in 0..num_episodes {
-            let episode = self.generate_episode(policy, prob_explore);
+            let i = *episode_count;
+            *episode_count += 1;
+            let episode = {
+                let behavior = exploration.behavior(value, visits, self.ties, i);
+                self.generate_episode(behavior.as_ref(), rng)
+            };
+            let mut stats = EpisodeStats {
+                episode: i,
+                ret: episode.ret(self.task.gamma()),
+                length: episode.steps.len(),
+                truncated: episode.truncated,
+                exploration: exploration.parameter(i),
+                alpha: None,
+                max_abs_td_error: 0.0,
+            };
+            for step in &episode.steps {
+                exploration.initialize(value, &step.state, self.task.action_space(&step.state));
+                let state_then_action = StateActionPair {
+                    state: step.state,
+                    action: step.action,
+                };
+                *visits.entry(state_then_action).or_insert(0) += 1;
+            }
 
             let mut step_ret = 0.0;
+            if episode.truncated {
+                // Bootstrap from the state the time limit cut us off at
+                let s = &episode.last_state;
+                (step_ret, _) =
+                    max_value_by_actions(value, s, self.task.action_space(s), &self.ties);
+            }
             let mut learning_importance = 1.0;
-            for step in episode.iter().rev() {
+            for step in episode.steps.iter().rev() {
                 step_ret = self.task.gamma() * step_ret + step.reward;
                 let state_then_action = StateActionPair {
                     state: step.state,
@@ -59,8 +172,9 @@ where
                 {
                     // Nudge value towards the step return for this action on the current state
                     let v = value.get(&state_then_action).unwrap_or(&0.0);
-                    let shift = learning_rate * (step_ret - v);
-                    value.insert(state_then_action, v + shift);
+                    let error = step_ret - v;
+                    value.insert(state_then_action, v + learning_rate * error);
+                    stats.max_abs_td_error = f64::max(stats.max_abs_td_error, error.abs());
                 }
                 let best_actions = {
                     // Set the best actions to the policy
@@ -68,8 +182,9 @@ where
                         value,
                         &step.state,
                         self.task.action_space(&step.state),
+                        &self.ties,
                     );
-                    policy.insert(step.state, a);
+                    policy.insert(step.state, self.ties.break_ties(a, rng));
                     policy.get(&step.state).unwrap()
                 };
                 if !best_actions.contains(&step.action) {
@@ -78,52 +193,111 @@ where
                 }
                 {
                     // Adjust learning rate
-                    let prob_explored_best_actions = {
-                        let num_best = best_actions.len();
-                        let num_all = self.task.action_space_len(&step.state);
-                        prob_explore * num_best as f64 / num_all as f64
-                    };
+                    let prob_target = 1. / best_actions.len() as f64;
                     // Learn more from the immediate past with a correctly explored future
-                    learning_importance *= 1. / prob_explored_best_actions;
+                    learning_importance *= prob_target / step.probability;
                 }
             }
+
+            history.episodes.push(stats);
+            if let Some(observer) = &mut observer {
+                if observer(&stats).is_break() {
+                    break;
+                }
+            }
+        }
+        history
+    }
+
+    /// Every-visit Monte Carlo prediction of $v_\pi$
+    ///
+    /// - `value`: $V$
+    /// - `visits`: number of returns averaged into $V(s)$
+    /// - `seed`: of the RNG the episodes are drawn from
+    ///
+    /// Return the number of truncated episodes
+    pub fn evaluate_policy(
+        &self,
+        policy: &dyn Policy<State, Action>,
+        value: &mut HashMap<State, f64>,
+        visits: &mut HashMap<State, usize>,
+        num_episodes: usize,
+        seed: u64,
+    ) -> usize {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut truncated = 0;
+        for _ in 0..num_episodes {
+            let episode = self.generate_episode(policy, &mut rng);
+
+            let mut step_ret = 0.0;
+            if episode.truncated {
+                truncated += 1;
+                // Bootstrap from the state the time limit cut us off at
+                step_ret = *value.get(&episode.last_state).unwrap_or(&0.0);
+            }
+            for step in episode.steps.iter().rev() {
+                step_ret = self.task.gamma() * step_ret + step.reward;
+                let n = visits.entry(step.state).or_insert(0);
+                *n += 1;
+                let v = value.entry(step.state).or_insert(0.0);
+                *v += (step_ret - *v) / *n as f64;
+            }
         }
+        truncated
     }
 
-    fn generate_episode(
+    pub fn generate_episode(
         &self,
-        policy: &HashMap<State, Vec<Action>>,
-        prob_explore: f64,
-    ) -> Vec<Step<State, Action>> {
-        // using epsilon-greedy policy b
-        let mut episode = vec![];
-        let mut s = self.task.random_state();
+        policy: &dyn Policy<State, Action>,
+        rng: &mut dyn RngCore,
+    ) -> Episode<State, Action> {
+        let mut steps = vec![];
+        let mut s = self.task.random_state(rng);
         while !self.task.in_terminal_state_space(&s) {
-            let mut rng = rand::thread_rng();
-            let rnd = rng.gen_range(0.0..1.0);
-            // epsilon-greedy policy
-            let a = if rnd < prob_explore {
-                self.task.random_action(&s)
-            } else {
-                match policy.get(&s) {
-                    Some(a) => *a.choose(&mut rng).unwrap(),
-                    None => self.task.random_action(&s),
-                }
-            };
-            let (s_next, r) = self.task.transit(&s, &a);
-            episode.push(Step {
+            if self.max_steps.is_some_and(|max| steps.len() >= max) {
+                break;
+            }
+            let a = policy.sample(&s, self.task.action_space(&s), rng);
+            let probability = policy.probability(&s, &a, self.task.action_space(&s));
+            let (s_next, r) = self.task.transit(&s, &a, rng);
+            steps.push(Step {
                 state: s,
                 action: a,
                 reward: r,
+                probability,
             });
             s = s_next;
         }
-        episode
+        Episode {
+            steps,
+            truncated: !self.task.in_terminal_state_space(&s),
+            last_state: s,
+        }
+    }
+}
+
+pub struct Episode<State, Action> {
+    pub steps: Vec<Step<State, Action>>,
+    /// The state the episode ended at
+    pub last_state: State,
+    /// Whether the episode was cut off by the step limit rather than reaching a terminal state
+    pub truncated: bool,
+}
+
+impl<State, Action> Episode<State, Action> {
+    /// Discounted return from the start state
+    pub fn ret(&self, gamma: f64) -> f64 {
+        self.steps
+            .iter()
+            .rev()
+            .fold(0.0, |ret, step| gamma * ret + step.reward)
     }
 }
 
-struct Step<State, Action> {
+pub struct Step<State, Action> {
     pub state: State,
     pub action: Action,
     pub reward: f64,
+    /// Probability of the policy taking `action` at `state`
+    pub probability: f64,
 }
@@ -0,0 +1,706 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use plotly::{
+    common::{ColorScale, ColorScalePalette, Fill, Line, Marker, Mode as PlotlyMode, Title},
+    layout::Axis,
+    Contour, HeatMap, Layout, Plot, Rgba, Scatter, Surface,
+};
+
+use crate::history::History;
+
+const SVG_WIDTH: f64 = 640.0;
+const SVG_HEIGHT: f64 = 400.0;
+const SVG_MARGIN: f64 = 56.0;
+/// plotly.js, embedded in every HTML file so that it renders offline
+const PLOTLY_JS: &str = include_str!("../assets/plotly-2.8.3.min.js");
+const COLORS: [&str; 8] = [
+    "#636efa", "#ef553b", "#00cc96", "#ab63fa", "#ffa15a", "#19d3f3", "#ff6692", "#b6e880",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Lines,
+    Markers,
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub name: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub mode: Mode,
+    /// Lower and upper edges of a shaded band around `y`, such as a confidence interval
+    pub band: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+/// Something that can be drawn by plotly, or as a self-contained SVG image for places without a browser
+pub trait Chart {
+    fn plotly(&self) -> Plot;
+    fn svg(&self) -> String;
+
+    /// Write to `path` as standalone HTML, plotly.js included, or as SVG, by its extension `.html` or `.svg`
+    fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()>
+    where
+        Self: Sized,
+    {
+        save_panels(path, &[self], 1)
+    }
+}
+
+/// Write `panels` to a single file, in rows of `columns`, as HTML or SVG by the extension of `path`
+pub fn save_panels(
+    path: impl AsRef<Path>,
+    panels: &[&dyn Chart],
+    columns: usize,
+) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let mut file = BufWriter::new(File::create(path)?);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => {
+            writeln!(
+                file,
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\"/>"
+            )?;
+            writeln!(
+                file,
+                "<script type=\"text/javascript\">{}</script>",
+                PLOTLY_JS
+            )?;
+            writeln!(file, "</head>\n<body>")?;
+            writeln!(
+                file,
+                r#"<div style="display: grid; grid-template-columns: repeat({}, 1fr)">"#,
+                columns.max(1)
+            )?;
+            for panel in panels {
+                writeln!(file, "<div>{}</div>", panel.plotly().to_inline_html(None))?;
+            }
+            writeln!(file, "</div>\n</body>\n</html>")?;
+        }
+        Some("svg") => {
+            let columns = columns.clamp(1, panels.len().max(1));
+            let rows = panels.len().div_ceil(columns);
+            writeln!(
+                file,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+                w = SVG_WIDTH * columns as f64,
+                h = SVG_HEIGHT * rows as f64
+            )?;
+            for (i, panel) in panels.iter().enumerate() {
+                writeln!(
+                    file,
+                    r#"<g transform="translate({} {})">"#,
+                    SVG_WIDTH * (i % columns) as f64,
+                    SVG_HEIGHT * (i / columns) as f64
+                )?;
+                file.write_all(panel.svg().as_bytes())?;
+                writeln!(file, "</g>")?;
+            }
+            writeln!(file, "</svg>")?;
+        }
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("cannot plot to {}", path.display()),
+            ))
+        }
+    }
+    file.flush()
+}
+
+/// A 2-D chart of one or more series
+#[derive(Debug, Clone, Default)]
+pub struct Figure {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub series: Vec<Series>,
+}
+
+impl Figure {
+    pub fn new(title: &str, x_label: &str, y_label: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            series: vec![],
+        }
+    }
+
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.series.push(series);
+        self
+    }
+}
+
+impl Chart for Figure {
+    fn plotly(&self) -> Plot {
+        let mut plot = Plot::new();
+        for (i, series) in self.series.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            if let Some((lower, upper)) = &series.band {
+                // filled from the lower edge up to the upper edge traced just before it
+                let edge = |y: &Vec<f64>| {
+                    Scatter::new(series.x.clone(), y.clone())
+                        .mode(PlotlyMode::Lines)
+                        .line(Line::new().width(0.0).color(color))
+                        .legend_group(&series.name)
+                        .show_legend(false)
+                };
+                plot.add_trace(edge(upper));
+                plot.add_trace(
+                    edge(lower)
+                        .fill(Fill::ToNextY)
+                        .fill_color(translucent(color)),
+                );
+            }
+            let trace = Scatter::new(series.x.clone(), series.y.clone())
+                .name(&series.name)
+                .legend_group(&series.name);
+            let trace = match series.mode {
+                Mode::Lines => trace.mode(PlotlyMode::Lines).line(Line::new().color(color)),
+                Mode::Markers => trace
+                    .mode(PlotlyMode::Markers)
+                    .marker(Marker::new().color(color)),
+            };
+            plot.add_trace(trace);
+        }
+        plot.set_layout(
+            Layout::new()
+                .title(Title::new(&self.title))
+                .x_axis(Axis::new().title(Title::new(&self.x_label)))
+                .y_axis(Axis::new().title(Title::new(&self.y_label))),
+        );
+        plot
+    }
+
+    fn svg(&self) -> String {
+        let points = || {
+            self.series.iter().flat_map(|s| {
+                let band = s
+                    .band
+                    .iter()
+                    .flat_map(|(lower, upper)| s.x.iter().zip(lower).chain(s.x.iter().zip(upper)));
+                s.x.iter().zip(&s.y).chain(band)
+            })
+        };
+        let (x_min, x_max) = padded_range(points().map(|(x, _)| *x));
+        let (y_min, y_max) = padded_range(points().map(|(_, y)| *y));
+        let (left, right) = (SVG_MARGIN, SVG_WIDTH - SVG_MARGIN / 2.0);
+        let (top, bottom) = (SVG_MARGIN / 2.0, SVG_HEIGHT - SVG_MARGIN);
+        let px = |x: f64| left + (x - x_min) / (x_max - x_min) * (right - left);
+        let py = |y: f64| bottom - (y - y_min) / (y_max - y_min) * (bottom - top);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+            w = SVG_WIDTH,
+            h = SVG_HEIGHT
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="16" text-anchor="middle" font-size="14">{}</text>"#,
+            SVG_WIDTH / 2.0,
+            escape(&self.title)
+        );
+        for (x, label) in ticks(x_min, x_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{x:.1}" y1="{top}" x2="{x:.1}" y2="{bottom}" stroke="#eee"/><text x="{x:.1}" y="{}" text-anchor="middle">{label}</text>"##,
+                bottom + 16.0,
+                x = px(x),
+            );
+        }
+        for (y, label) in ticks(y_min, y_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{left}" y1="{y:.1}" x2="{right}" y2="{y:.1}" stroke="#eee"/><text x="{}" y="{y:.1}" text-anchor="end" dominant-baseline="middle">{label}</text>"##,
+                left - 4.0,
+                y = py(y),
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{left}" y="{top}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            right - left,
+            bottom - top
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            (left + right) / 2.0,
+            SVG_HEIGHT - 12.0,
+            escape(&self.x_label)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text transform="translate(14 {}) rotate(-90)" text-anchor="middle">{}</text>"#,
+            (top + bottom) / 2.0,
+            escape(&self.y_label)
+        );
+
+        for (i, series) in self.series.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            if let Some((lower, upper)) = &series.band {
+                let outline: Vec<String> = series
+                    .x
+                    .iter()
+                    .zip(lower)
+                    .chain(series.x.iter().zip(upper).rev())
+                    .map(|(x, y)| format!("{:.1},{:.1}", px(*x), py(*y)))
+                    .collect();
+                let _ = writeln!(
+                    svg,
+                    r#"<polygon points="{}" fill="{color}" fill-opacity="0.2"/>"#,
+                    outline.join(" ")
+                );
+            }
+            let points = series.x.iter().zip(&series.y);
+            match series.mode {
+                Mode::Lines => {
+                    let points: Vec<String> = points
+                        .map(|(x, y)| format!("{:.1},{:.1}", px(*x), py(*y)))
+                        .collect();
+                    let _ = writeln!(
+                        svg,
+                        r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"#,
+                        points.join(" ")
+                    );
+                }
+                Mode::Markers => {
+                    for (x, y) in points {
+                        let _ = writeln!(
+                            svg,
+                            r#"<circle cx="{:.1}" cy="{:.1}" r="2.5" fill="{color}"/>"#,
+                            px(*x),
+                            py(*y)
+                        );
+                    }
+                }
+            }
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="end" fill="{color}">{}</text>"#,
+                right - 6.0,
+                top + 16.0 * (i + 1) as f64,
+                escape(&series.name)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridStyle {
+    Heatmap,
+    Contour,
+    /// 3-D in HTML; SVG falls back to a heatmap
+    Surface,
+}
+
+/// Values over a rectangular grid, $z_{ij}$ at $(x_j, y_i)$
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    /// One row per `y`, `NAN` where there is no value
+    pub z: Vec<Vec<f64>>,
+    pub style: GridStyle,
+}
+
+impl Grid {
+    /// Lay out `points` of `(x, y, z)` on the grid of their distinct coordinates
+    pub fn from_points(
+        title: &str,
+        x_label: &str,
+        y_label: &str,
+        points: impl Iterator<Item = (f64, f64, f64)>,
+        style: GridStyle,
+    ) -> Self {
+        let points: Vec<(f64, f64, f64)> = points.collect();
+        let axis = |coordinate: fn(&(f64, f64, f64)) -> f64| {
+            let mut values: Vec<f64> = points.iter().map(coordinate).collect();
+            values.sort_by(f64::total_cmp);
+            values.dedup();
+            values
+        };
+        let x = axis(|p| p.0);
+        let y = axis(|p| p.1);
+        let mut z = vec![vec![f64::NAN; x.len()]; y.len()];
+        for (px, py, pz) in points {
+            let j = x.partition_point(|x| *x < px);
+            let i = y.partition_point(|y| *y < py);
+            z[i][j] = pz;
+        }
+        Self {
+            title: title.to_string(),
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            x,
+            y,
+            z,
+            style,
+        }
+    }
+
+    pub fn with_style(mut self, style: GridStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Chart for Grid {
+    fn plotly(&self) -> Plot {
+        let mut plot = Plot::new();
+        let color_scale = ColorScale::Palette(ColorScalePalette::Viridis);
+        match self.style {
+            GridStyle::Heatmap => plot.add_trace(
+                HeatMap::new(self.x.clone(), self.y.clone(), self.z.clone())
+                    .name(&self.title)
+                    .color_scale(color_scale),
+            ),
+            GridStyle::Contour => plot.add_trace(
+                Contour::new(self.x.clone(), self.y.clone(), self.z.clone())
+                    .name(&self.title)
+                    .color_scale(color_scale),
+            ),
+            GridStyle::Surface => plot.add_trace(
+                Surface::new(self.z.clone())
+                    .x(self.x.clone())
+                    .y(self.y.clone())
+                    .name(&self.title)
+                    .color_scale(color_scale),
+            ),
+        }
+        plot.set_layout(
+            Layout::new()
+                .title(Title::new(&self.title))
+                .x_axis(Axis::new().title(Title::new(&self.x_label)))
+                .y_axis(Axis::new().title(Title::new(&self.y_label))),
+        );
+        plot
+    }
+
+    fn svg(&self) -> String {
+        let (z_min, z_max) = self
+            .z
+            .iter()
+            .flatten()
+            .filter(|z| z.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), z| {
+                (min.min(*z), max.max(*z))
+            });
+        let (left, right) = (SVG_MARGIN, SVG_WIDTH - SVG_MARGIN * 1.5);
+        let (top, bottom) = (SVG_MARGIN / 2.0, SVG_HEIGHT - SVG_MARGIN);
+        let cell_width = (right - left) / self.x.len().max(1) as f64;
+        let cell_height = (bottom - top) / self.y.len().max(1) as f64;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+            w = SVG_WIDTH,
+            h = SVG_HEIGHT
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="16" text-anchor="middle" font-size="14">{}</text>"#,
+            SVG_WIDTH / 2.0,
+            escape(&self.title)
+        );
+        for (i, row) in self.z.iter().enumerate() {
+            for (j, z) in row.iter().enumerate() {
+                if !z.is_finite() {
+                    continue;
+                }
+                let t = if z_max > z_min {
+                    (z - z_min) / (z_max - z_min)
+                } else {
+                    0.5
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}</title></rect>"#,
+                    left + j as f64 * cell_width,
+                    // the first row at the bottom
+                    bottom - (i + 1) as f64 * cell_height,
+                    cell_width + 0.5,
+                    cell_height + 0.5,
+                    viridis(t),
+                    z
+                );
+            }
+        }
+        // label every few cells along each axis
+        let x_every = self.x.len().div_ceil(10).max(1);
+        for (j, x) in self.x.iter().enumerate().step_by(x_every) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+                left + (j as f64 + 0.5) * cell_width,
+                bottom + 16.0,
+                x
+            );
+        }
+        let y_every = self.y.len().div_ceil(10).max(1);
+        for (i, y) in self.y.iter().enumerate().step_by(y_every) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+                left - 4.0,
+                bottom - (i as f64 + 0.5) * cell_height,
+                y
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            (left + right) / 2.0,
+            SVG_HEIGHT - 12.0,
+            escape(&self.x_label)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text transform="translate(14 {}) rotate(-90)" text-anchor="middle">{}</text>"#,
+            (top + bottom) / 2.0,
+            escape(&self.y_label)
+        );
+        // color bar
+        let bar_left = right + SVG_MARGIN / 2.0;
+        for k in 0..50 {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{bar_left}" y="{:.1}" width="12" height="{:.1}" fill="{}"/>"#,
+                bottom - (k + 1) as f64 * (bottom - top) / 50.0,
+                (bottom - top) / 50.0 + 0.5,
+                viridis(k as f64 / 49.0)
+            );
+        }
+        for (z, y) in [(z_max, top), (z_min, bottom)] {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{y}" dominant-baseline="middle">{:.3}</text>"#,
+                bar_left + 16.0,
+                z
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// $V(s)$ against $s$
+pub fn value_curve<State>(value: &HashMap<State, f64>) -> Figure
+where
+    State: Copy + Into<f64>,
+{
+    let mut points: Vec<(f64, f64)> = value.iter().map(|(s, v)| ((*s).into(), *v)).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (x, y) = points.into_iter().unzip();
+    Figure::new("Value", "s", "V(s)").with_series(Series {
+        name: "V(s)".to_string(),
+        x,
+        y,
+        mode: Mode::Lines,
+        band: None,
+    })
+}
+
+/// Every action of $\pi(s)$ against $s$
+pub fn policy_scatter<State, Action>(policy: &HashMap<State, Vec<Action>>) -> Figure
+where
+    State: Copy + Into<f64>,
+    Action: Copy + Into<f64>,
+{
+    let mut x = vec![];
+    let mut y = vec![];
+    for (s, a) in policy {
+        for a in a {
+            x.push((*s).into());
+            y.push((*a).into());
+        }
+    }
+    Figure::new("Policy", "s", "a").with_series(Series {
+        name: "\\pi(s)".to_string(),
+        x,
+        y,
+        mode: Mode::Markers,
+        band: None,
+    })
+}
+
+/// $V(s)$ over states with two coordinates
+pub fn value_grid<State>(
+    value: &HashMap<State, f64>,
+    coordinates: impl Fn(&State) -> (f64, f64),
+    style: GridStyle,
+) -> Grid {
+    let points = value.iter().map(|(s, v)| {
+        let (x, y) = coordinates(s);
+        (x, y, *v)
+    });
+    Grid::from_points("Value", "x", "y", points, style)
+}
+
+/// $\pi(s)$ over states with two coordinates, averaging tied actions
+pub fn policy_grid<State, Action>(
+    policy: &HashMap<State, Vec<Action>>,
+    coordinates: impl Fn(&State) -> (f64, f64),
+    style: GridStyle,
+) -> Grid
+where
+    Action: Copy + Into<f64>,
+{
+    let points = policy.iter().filter(|(_, a)| !a.is_empty()).map(|(s, a)| {
+        let (x, y) = coordinates(s);
+        let mean = a.iter().map(|a| (*a).into()).sum::<f64>() / a.len() as f64;
+        (x, y, mean)
+    });
+    Grid::from_points("Policy", "x", "y", points, style)
+}
+
+/// Moving average of the training returns over `window` episodes, at least one
+pub fn learning_curve(history: &History, window: usize) -> Figure {
+    let window = window.max(1);
+    let y = moving_average(&history.returns(), window);
+    let x = (0..y.len()).map(|i| (i + window) as f64).collect();
+    Figure::new("Learning curve", "episode", "return").with_series(Series {
+        name: format!("return, mean of {}", window),
+        x,
+        y,
+        mode: Mode::Lines,
+        band: None,
+    })
+}
+
+/// One curve per group of runs, such as an algorithm over several seeds
+///
+/// Each curve is the mean over runs of the returns smoothed over `window` episodes,
+/// in a band of the 95% confidence interval of that mean across runs.
+/// Runs are cut to the shortest one of their group, and a `window` of 0 is taken as 1.
+pub fn learning_curves(groups: &[(&str, &[History])], window: usize) -> Figure {
+    let window = window.max(1);
+    let mut figure = Figure::new("Learning curves", "episode", "return");
+    for (name, runs) in groups {
+        let smoothed: Vec<Vec<f64>> = runs
+            .iter()
+            .map(|run| moving_average(&run.returns(), window))
+            .collect();
+        let len = smoothed.iter().map(Vec::len).min().unwrap_or(0);
+        let n = smoothed.len() as f64;
+        let mut y = vec![];
+        let mut lower = vec![];
+        let mut upper = vec![];
+        for i in 0..len {
+            let mean = smoothed.iter().map(|run| run[i]).sum::<f64>() / n;
+            let variance = smoothed
+                .iter()
+                .map(|run| (run[i] - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            let half_width = 1.96 * f64::sqrt(variance / n);
+            y.push(mean);
+            lower.push(mean - half_width);
+            upper.push(mean + half_width);
+        }
+        figure.series.push(Series {
+            name: format!("{} ({} runs)", name, runs.len()),
+            x: (0..len).map(|i| (i + window) as f64).collect(),
+            y,
+            mode: Mode::Lines,
+            band: (runs.len() > 1).then_some((lower, upper)),
+        });
+    }
+    figure
+}
+
+/// Mean of every `window` consecutive values; the values themselves if `window` is 0
+fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    values
+        .windows(window)
+        .map(|w| w.iter().sum::<f64>() / window as f64)
+        .collect()
+}
+
+/// The range of `values`, widened a little so that nothing sits on the frame
+fn padded_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        return (0.0, 1.0);
+    }
+    let pad = if min == max { 0.5 } else { (max - min) * 0.05 };
+    (min - pad, max + pad)
+}
+
+/// Round tick positions in `[min, max]` with their labels
+fn ticks(min: f64, max: f64) -> Vec<(f64, String)> {
+    let rough = (max - min) / 5.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap();
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    let mut ticks = vec![];
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    for i in first..=last {
+        // `+ 0.0` turns `-0` into `0`
+        let tick = i as f64 * step + 0.0;
+        ticks.push((tick, format!("{:.*}", decimals, tick)));
+    }
+    ticks
+}
+
+/// `color` of the form `#rrggbb` at 20% opacity, for bands
+fn translucent(color: &str) -> Rgba {
+    let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).unwrap();
+    Rgba::new(channel(1), channel(3), channel(5), 0.2)
+}
+
+/// Approximation of the viridis color map at `t` in `[0, 1]`
+fn viridis(t: f64) -> String {
+    const STOPS: [(f64, f64, f64); 5] = [
+        (68.0, 1.0, 84.0),
+        (59.0, 82.0, 139.0),
+        (33.0, 145.0, 140.0),
+        (94.0, 201.0, 98.0),
+        (253.0, 231.0, 37.0),
+    ];
+    let t = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let k = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - k as f64;
+    let (a, b) = (STOPS[k], STOPS[k + 1]);
+    let mix = |a: f64, b: f64| (a + (b - a) * f).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(a.0, b.0),
+        mix(a.1, b.1),
+        mix(a.2, b.2)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
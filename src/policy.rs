@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use crate::{max_value_by_actions, ties::Ties, StateActionPair};
+
+/// A stochastic policy $\pi(a \mid s)$
+///
+/// `action_space` is the action space of `s` as provided by the task.
+pub trait Policy<State, Action>
+where
+    Action: Copy + std::cmp::Eq + 'static,
+{
+    /// $\pi(\cdot \mid s)$; actions left out have probability zero
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)>;
+
+    /// $\pi(a \mid s)$
+    fn probability(
+        &self,
+        s: &State,
+        a: &Action,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> f64 {
+        self.distribution(s, action_space)
+            .iter()
+            .filter(|(action, _)| action == a)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    fn sample(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+        rng: &mut dyn RngCore,
+    ) -> Action {
+        let distribution = self.distribution(s, action_space);
+        distribution.choose_weighted(rng, |(_, p)| *p).unwrap().0
+    }
+}
+
+impl<State, Action, P> Policy<State, Action> for &P
+where
+    Action: Copy + std::cmp::Eq + 'static,
+    P: Policy<State, Action> + ?Sized,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        (**self).distribution(s, action_space)
+    }
+
+    fn sample(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+        rng: &mut dyn RngCore,
+    ) -> Action {
+        (**self).sample(s, action_space, rng)
+    }
+}
+
+fn uniform<Action>(actions: impl Iterator<Item = Action>) -> Vec<(Action, f64)> {
+    let actions: Vec<Action> = actions.collect();
+    let p = 1.0 / actions.len() as f64;
+    actions.into_iter().map(|a| (a, p)).collect()
+}
+
+pub struct UniformRandom;
+impl<State, Action> Policy<State, Action> for UniformRandom
+where
+    Action: Copy + std::cmp::Eq + 'static,
+{
+    fn distribution(
+        &self,
+        _s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        uniform(action_space)
+    }
+}
+
+/// Uniform over the greedy actions w.r.t. $Q$
+///
+/// Under `TieBreaking::Random` this is uniform over every tied action, so that `sample` draws
+/// the random tie-break and `probability` agrees with it.
+pub struct Greedy<'a, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub value: &'a HashMap<StateActionPair<State, Action>, f64>,
+    pub ties: Ties,
+}
+impl<State, Action> Policy<State, Action> for Greedy<'_, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        let (_, a) = max_value_by_actions(self.value, s, action_space, &self.ties);
+        uniform(a.into_iter())
+    }
+}
+
+/// Follows `greedy` with probability $1 - \epsilon$, or else a uniformly random action
+pub struct EpsilonGreedy<P> {
+    pub greedy: P,
+    pub epsilon: f64,
+}
+impl<State, Action, P> Policy<State, Action> for EpsilonGreedy<P>
+where
+    Action: Copy + std::cmp::Eq + 'static,
+    P: Policy<State, Action>,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        let actions: Vec<Action> = action_space.collect();
+        let greedy = self
+            .greedy
+            .distribution(s, Box::new(actions.clone().into_iter()));
+        let explore = self.epsilon / actions.len() as f64;
+        actions
+            .into_iter()
+            .map(|a| {
+                let p_greedy: f64 = greedy
+                    .iter()
+                    .filter(|(action, _)| *action == a)
+                    .map(|(_, p)| p)
+                    .sum();
+                (a, (1.0 - self.epsilon) * p_greedy + explore)
+            })
+            .collect()
+    }
+
+    fn sample(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+        rng: &mut dyn RngCore,
+    ) -> Action {
+        if rng.gen_range(0.0..1.0) < self.epsilon {
+            let actions: Vec<Action> = action_space.collect();
+            *actions.choose(rng).unwrap()
+        } else {
+            self.greedy.sample(s, action_space, rng)
+        }
+    }
+}
+
+/// Boltzmann distribution $\pi(a \mid s) \propto e^{Q(s, a) / \tau}$
+///
+/// Its limit as $\tau \to 0$ for $\tau \leq 0$: uniform over the actions of max $Q(s, a)$.
+pub struct Softmax<'a, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub value: &'a HashMap<StateActionPair<State, Action>, f64>,
+    /// $\tau$
+    pub temperature: f64,
+}
+impl<State, Action> Policy<State, Action> for Softmax<'_, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq + 'static,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        let q: Vec<(Action, f64)> = action_space
+            .map(|a| {
+                let v = *self
+                    .value
+                    .get(&StateActionPair {
+                        state: *s,
+                        action: a,
+                    })
+                    .unwrap_or(&0.0);
+                (a, v)
+            })
+            .collect();
+        // shift by the max to keep `exp` from overflowing
+        let max_q = q.iter().map(|(_, v)| *v).fold(f64::MIN, f64::max);
+        if self.temperature <= 0.0 {
+            return uniform(q.into_iter().filter(|(_, v)| *v == max_q).map(|(a, _)| a));
+        }
+        let weights: Vec<(Action, f64)> = q
+            .into_iter()
+            .map(|(a, v)| (a, f64::exp((v - max_q) / self.temperature)))
+            .collect();
+        let sum: f64 = weights.iter().map(|(_, w)| w).sum();
+        weights.into_iter().map(|(a, w)| (a, w / sum)).collect()
+    }
+}
+
+/// Uniform over the listed actions of each state, or over the whole action space if unlisted
+impl<State, Action> Policy<State, Action> for HashMap<State, Vec<Action>>
+where
+    State: std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::cmp::Eq + 'static,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        match self.get(s) {
+            Some(a) => uniform(a.iter().copied()),
+            None => uniform(action_space),
+        }
+    }
+}
+
+/// Explicit $\pi(a \mid s)$ per state, or uniform over the action space if unlisted
+pub struct Tabular<State, Action> {
+    pub table: HashMap<State, Vec<(Action, f64)>>,
+}
+impl<State, Action> Policy<State, Action> for Tabular<State, Action>
+where
+    State: std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::cmp::Eq + 'static,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        match self.table.get(s) {
+            Some(distribution) => distribution.clone(),
+            None => uniform(action_space),
+        }
+    }
+}
+
+/// Greedy w.r.t. the upper confidence bound $Q(s, a) + c \sqrt{\ln N(s) / N(s, a)}$
+///
+/// Actions never taken at `s` are preferred over all others.
+pub struct Ucb<'a, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub value: &'a HashMap<StateActionPair<State, Action>, f64>,
+    /// $N(s, a)$
+    pub visits: &'a HashMap<StateActionPair<State, Action>, usize>,
+    pub c: f64,
+    pub ties: Ties,
+}
+impl<State, Action> Policy<State, Action> for Ucb<'_, State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
+{
+    fn distribution(
+        &self,
+        s: &State,
+        action_space: Box<dyn Iterator<Item = Action>>,
+    ) -> Vec<(Action, f64)> {
+        let counts: Vec<(Action, usize)> = action_space
+            .map(|a| {
+                let n = *self
+                    .visits
+                    .get(&StateActionPair {
+                        state: *s,
+                        action: a,
+                    })
+                    .unwrap_or(&0);
+                (a, n)
+            })
+            .collect();
+        let unvisited: Vec<Action> = counts
+            .iter()
+            .filter(|(_, n)| *n == 0)
+            .map(|(a, _)| *a)
+            .collect();
+        if !unvisited.is_empty() {
+            return uniform(unvisited.into_iter());
+        }
+        let n_s: usize = counts.iter().map(|(_, n)| n).sum();
+        let (_, a) = self.ties.max(counts.into_iter().map(|(a, n)| {
+            let q = *self
+                .value
+                .get(&StateActionPair {
+                    state: *s,
+                    action: a,
+                })
+                .unwrap_or(&0.0);
+            let bonus = self.c * f64::sqrt(f64::ln(n_s as f64) / n as f64);
+            (a, q + bonus)
+        }));
+        uniform(a.into_iter())
+    }
+}
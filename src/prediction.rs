@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// A Markov reward process: a task whose actions are already chosen by the policy being evaluated
+pub trait PredictionTask<State> {
+    fn gamma(&self) -> f64;
+    fn random_state(&self, rng: &mut dyn RngCore) -> State;
+    fn transit(&self, s: &State, rng: &mut dyn RngCore) -> (State, f64);
+    fn in_terminal_state_space(&self, s: &State) -> bool;
+}
+
+/// How to estimate $v_\pi$ from sample episodes, all with a constant step size $\alpha$
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    /// TD(0): $V(S_t) \leftarrow V(S_t) + \alpha [R_{t+1} + \gamma V(S_{t+1}) - V(S_t)]$
+    Td,
+    /// Toward the $n$-step return $G_{t:t+n}$
+    NStepTd { n: usize },
+    /// Online, with accumulating eligibility traces decaying by $\gamma \lambda$
+    TdLambda { lambda: f64 },
+    /// Every-visit constant-$\alpha$ MC, toward $G_t$ at the end of each episode
+    MonteCarlo,
+}
+
+pub struct Prediction<State> {
+    task: Box<dyn PredictionTask<State>>,
+}
+
+impl<State> Prediction<State>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub fn new(task: Box<dyn PredictionTask<State>>) -> Self {
+        Self { task }
+    }
+
+    pub fn task(&self) -> &dyn PredictionTask<State> {
+        self.task.as_ref()
+    }
+
+    /// Run one episode, updating `v` in place
+    ///
+    /// States missing from `v` start at zero; terminal states are always worth zero.
+    pub fn episode(
+        &self,
+        method: Method,
+        alpha: f64,
+        v: &mut HashMap<State, f64>,
+        rng: &mut dyn RngCore,
+    ) {
+        let gamma = self.task.gamma();
+        let mut states = vec![self.task.random_state(rng)];
+        let mut rewards = vec![];
+        let mut traces: HashMap<State, f64> = HashMap::new();
+        // step t is the transition from states[t] to states[t + 1] with rewards[t]
+        let mut t = 0;
+        while !self.task.in_terminal_state_space(&states[t]) {
+            let s = states[t];
+            let (s_next, r) = self.task.transit(&s, rng);
+            states.push(s_next);
+            rewards.push(r);
+            match method {
+                Method::Td => {
+                    let td_error = r + gamma * self.value(v, &s_next) - self.value(v, &s);
+                    *v.entry(s).or_insert(0.0) += alpha * td_error;
+                }
+                Method::NStepTd { n } => {
+                    // the state visited at t + 1 - n now has its n-step return
+                    if n <= t + 1 {
+                        self.n_step_update(v, &states, &rewards, t + 1 - n, t + 1, alpha);
+                    }
+                }
+                Method::TdLambda { lambda } => {
+                    let td_error = r + gamma * self.value(v, &s_next) - self.value(v, &s);
+                    *traces.entry(s).or_insert(0.0) += 1.0;
+                    for (s, e) in traces.iter_mut() {
+                        *v.entry(*s).or_insert(0.0) += alpha * td_error * *e;
+                        *e *= gamma * lambda;
+                    }
+                }
+                Method::MonteCarlo => (),
+            }
+            t += 1;
+        }
+        let end = t;
+        match method {
+            Method::Td | Method::TdLambda { .. } => (),
+            Method::NStepTd { n } => {
+                // returns truncated by the end of the episode
+                for tau in (end + 1).saturating_sub(n)..end {
+                    self.n_step_update(v, &states, &rewards, tau, end, alpha);
+                }
+            }
+            Method::MonteCarlo => {
+                let mut g = 0.0;
+                for t in (0..end).rev() {
+                    g = gamma * g + rewards[t];
+                    let v_s = v.entry(states[t]).or_insert(0.0);
+                    *v_s += alpha * (g - *v_s);
+                }
+            }
+        }
+    }
+
+    /// Update $V(S_\tau)$ toward the return of rewards up to `horizon`, bootstrapped from $V(S_{horizon})$
+    fn n_step_update(
+        &self,
+        v: &mut HashMap<State, f64>,
+        states: &[State],
+        rewards: &[f64],
+        tau: usize,
+        horizon: usize,
+        alpha: f64,
+    ) {
+        let gamma = self.task.gamma();
+        let mut g = self.value(v, &states[horizon]);
+        for r in rewards[tau..horizon].iter().rev() {
+            g = r + gamma * g;
+        }
+        let v_s = v.entry(states[tau]).or_insert(0.0);
+        *v_s += alpha * (g - *v_s);
+    }
+
+    fn value(&self, v: &HashMap<State, f64>, s: &State) -> f64 {
+        if self.task.in_terminal_state_space(s) {
+            return 0.0;
+        }
+        *v.get(s).unwrap_or(&0.0)
+    }
+
+    /// RMS error against `true_value` before and after each episode, averaged over `runs` runs
+    ///
+    /// Every run starts with `initial_value` for every state of `true_value`, with its own seed
+    /// counting up from `seed`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn benchmark(
+        &self,
+        method: Method,
+        alpha: f64,
+        true_value: &HashMap<State, f64>,
+        initial_value: f64,
+        num_episodes: usize,
+        runs: usize,
+        seed: u64,
+    ) -> Vec<f64> {
+        let mut errors = vec![0.0; num_episodes + 1];
+        for run in 0..runs {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed + run as u64);
+            let mut v: HashMap<State, f64> =
+                true_value.keys().map(|s| (*s, initial_value)).collect();
+            errors[0] += rms_error(&v, true_value);
+            for error in errors.iter_mut().skip(1) {
+                self.episode(method, alpha, &mut v, &mut rng);
+                *error += rms_error(&v, true_value);
+            }
+        }
+        errors.iter().map(|e| e / runs as f64).collect()
+    }
+}
+
+/// $\sqrt{\frac{1}{|S|} \sum_s (V(s) - v(s))^2}$ over the states of `true_value`
+pub fn rms_error<State>(v: &HashMap<State, f64>, true_value: &HashMap<State, f64>) -> f64
+where
+    State: std::hash::Hash + std::cmp::Eq,
+{
+    let sum: f64 = true_value
+        .iter()
+        .map(|(s, true_v)| (v.get(s).unwrap_or(&0.0) - true_v).powi(2))
+        .sum();
+    (sum / true_value.len() as f64).sqrt()
+}
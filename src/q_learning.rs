@@ -1,68 +1,192 @@
 use std::collections::HashMap;
 
-use rand::{seq::SliceRandom, Rng};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::{max_value_by_actions, StateActionPair};
+use crate::{
+    exploration::Exploration,
+    history::{EpisodeStats, History, Observer},
+    max_value_by_actions,
+    schedule::Schedule,
+    serialization::map_as_pairs,
+    ties::Ties,
+    StateActionPair,
+};
 
 pub trait QLearningTask<State, Action> {
     fn gamma(&self) -> f64;
     fn action_space(&self, s: &State) -> Box<dyn Iterator<Item = Action>>;
     fn action_space_len(&self, s: &State) -> usize;
-    fn random_action(&self, s: &State) -> Action;
-    fn random_state(&self) -> State;
-    fn transit(&self, s: &State, a: &Action) -> (State, f64);
+    fn random_action(&self, s: &State, rng: &mut dyn RngCore) -> Action;
+    fn random_state(&self, rng: &mut dyn RngCore) -> State;
+    fn transit(&self, s: &State, a: &Action, rng: &mut dyn RngCore) -> (State, f64);
     fn in_terminal_state_space(&self, s: &State) -> bool;
 }
 
+/// Everything a Q-learning run needs to pick up where it left off
+///
+/// Training in chunks on the same state is equivalent to training in one go.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "State: Serialize, Action: Serialize",
+    deserialize = "State: Deserialize<'de>, Action: Deserialize<'de>"
+))]
+pub struct QLearningState<State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    /// $Q$
+    #[serde(with = "map_as_pairs")]
+    pub value: HashMap<StateActionPair<State, Action>, f64>,
+    /// $N(s, a)$
+    #[serde(with = "map_as_pairs")]
+    pub visits: HashMap<StateActionPair<State, Action>, usize>,
+    /// Number of episodes run so far, which drives the schedules
+    pub episode: usize,
+    pub rng: ChaCha8Rng,
+}
+impl<State, Action> QLearningState<State, Action>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    pub fn new(seed: u64) -> Self {
+        Self {
+            value: HashMap::new(),
+            visits: HashMap::new(),
+            episode: 0,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
 pub struct QLearning<State, Action> {
     task: Box<dyn QLearningTask<State, Action>>,
+    ties: Ties,
+    max_steps: Option<usize>,
 }
 
 impl<State, Action> QLearning<State, Action>
 where
     State: Copy + std::hash::Hash + std::cmp::Eq,
-    Action: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Ord + 'static,
 {
     pub fn new(task: Box<dyn QLearningTask<State, Action>>) -> Self {
-        Self { task }
+        Self {
+            task,
+            ties: Ties::default(),
+            max_steps: None,
+        }
     }
 
+    pub fn with_ties(mut self, ties: Ties) -> Self {
+        self.ties = ties;
+        self
+    }
+
+    pub fn task(&self) -> &dyn QLearningTask<State, Action> {
+        self.task.as_ref()
+    }
+
+    /// Truncate every episode after `max_steps` steps
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Run `num_episodes` more episodes on `state`
+    ///
+    /// - `alpha`: $\alpha$, consulted on every update
+    /// - `observer`: called after each episode
     pub fn value_evaluation(
         &self,
-        value: &mut HashMap<StateActionPair<State, Action>, f64>,
-        prob_explore: f64,
-        alpha: f64,
+        state: &mut QLearningState<State, Action>,
+        exploration: &Exploration,
+        alpha: Schedule,
         num_episodes: usize,
-    ) {
+        mut observer: Option<Observer>,
+    ) -> History {
+        let QLearningState {
+            value,
+            visits,
+            episode: episode_count,
+            rng,
+        } = state;
+        let mut history = History::default();
         for _ in 0..num_episodes {
-            // using epsilon-greedy policy b
-            let mut s = self.task.random_state();
+            let episode = *episode_count;
+            *episode_count += 1;
+            let mut stats = EpisodeStats {
+                episode,
+                ret: 0.0,
+                length: 0,
+                truncated: false,
+                exploration: exploration.parameter(episode),
+                alpha: None,
+                max_abs_td_error: 0.0,
+            };
+            let mut discount = 1.0;
+            let mut alpha_sum = 0.0;
+
+            let mut s = self.task.random_state(rng);
+            exploration.initialize(value, &s, self.task.action_space(&s));
             while !self.task.in_terminal_state_space(&s) {
-                let mut rng = rand::thread_rng();
-                let rnd = rng.gen_range(0.0..1.0);
-                // epsilon-greedy policy
-                let a = if rnd < prob_explore {
-                    self.task.random_action(&s)
-                } else {
-                    let (_, a) = self.max_value_by_actions(value, &s);
-                    *a.choose(&mut rng).unwrap()
+                if self.max_steps.is_some_and(|max| stats.length >= max) {
+                    // The last update has bootstrapped from `s` as a non-terminal state already
+                    stats.truncated = true;
+                    break;
+                }
+                stats.length += 1;
+                let a = {
+                    let behavior = exploration.behavior(value, visits, self.ties, episode);
+                    behavior.sample(&s, self.task.action_space(&s), rng)
                 };
-                let (s_next, r) = self.task.transit(&s, &a);
+                let (s_next, r) = self.task.transit(&s, &a, rng);
+                let terminal = self.task.in_terminal_state_space(&s_next);
+                if !terminal {
+                    exploration.initialize(value, &s_next, self.task.action_space(&s_next));
+                }
                 // update Q(S, A)
                 {
                     let state_then_action = StateActionPair {
                         state: s,
                         action: a,
                     };
+                    let n = visits.entry(state_then_action).or_insert(0);
+                    *n += 1;
+                    let alpha = alpha.value(episode, *n);
                     let q_sa = *value.get(&state_then_action).unwrap_or(&0.0);
-                    let (next_max_value, _) = self.max_value_by_actions(value, &s_next);
-                    let new_q_sa = q_sa + alpha * (r + self.task.gamma() * next_max_value - q_sa);
-                    value.insert(state_then_action, new_q_sa);
+                    // terminal states are worth zero, whatever Q holds for them
+                    let next_max_value = if terminal {
+                        0.0
+                    } else {
+                        self.max_value_by_actions(value, &s_next).0
+                    };
+                    let td_error = r + self.task.gamma() * next_max_value - q_sa;
+                    value.insert(state_then_action, q_sa + alpha * td_error);
+
+                    alpha_sum += alpha;
+                    stats.max_abs_td_error = f64::max(stats.max_abs_td_error, td_error.abs());
                 }
+                stats.ret += discount * r;
+                discount *= self.task.gamma();
 
                 s = s_next;
             }
+
+            if 0 < stats.length {
+                stats.alpha = Some(alpha_sum / stats.length as f64);
+            }
+            history.episodes.push(stats);
+            if let Some(observer) = &mut observer {
+                if observer(&stats).is_break() {
+                    break;
+                }
+            }
         }
+        history
     }
 
     pub fn max_value_by_actions(
@@ -70,6 +194,6 @@ where
         value: &HashMap<StateActionPair<State, Action>, f64>,
         s: &State,
     ) -> (f64, Vec<Action>) {
-        max_value_by_actions(value, s, self.task.action_space(s))
+        max_value_by_actions(value, s, self.task.action_space(s), &self.ties)
     }
 }
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use crate::StateActionPair;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Compact text views of learned tables, for terminals
+pub trait Render<State, Action> {
+    fn render_policy(&self, policy: &HashMap<State, Vec<Action>>) -> String;
+    fn render_values(&self, value: &HashMap<State, f64>) -> String;
+}
+
+/// $\max_a Q(s, a)$ of every state in $Q$
+pub fn max_over_actions<State, Action>(
+    value: &HashMap<StateActionPair<State, Action>, f64>,
+) -> HashMap<State, f64>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::hash::Hash + std::cmp::Eq,
+{
+    let mut v: HashMap<State, f64> = HashMap::new();
+    for (sa, q) in value {
+        let max = v.entry(sa.state).or_insert(f64::NEG_INFINITY);
+        *max = max.max(*q);
+    }
+    v
+}
+
+/// One bar per value, scaled between the min and the max; `None` shows as a blank
+pub fn sparkline(values: &[Option<f64>]) -> String {
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    values
+        .iter()
+        .map(|v| match v {
+            Some(v) if max > min => {
+                let level = (v - min) / (max - min) * (BARS.len() - 1) as f64;
+                BARS[level.round() as usize]
+            }
+            Some(_) => BARS[0],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// A table with right-aligned columns, headed by `column_labels` and led by `row_labels`
+pub fn table(column_labels: &[String], row_labels: &[String], cells: &[Vec<String>]) -> String {
+    let label_width = row_labels
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = column_labels
+        .iter()
+        .chain(cells.iter().flatten())
+        .map(|c| c.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut text = format!("{:label_width$}", "");
+    for label in column_labels {
+        text += &format!(" {:>width$}", label);
+    }
+    text.push('\n');
+    for (label, row) in row_labels.iter().zip(cells) {
+        text += &format!("{:>label_width$}", label);
+        for cell in row {
+            text += &format!(" {:>width$}", cell);
+        }
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// A hyperparameter that changes over the course of learning
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    Constant(f64),
+    /// From `start` to `end` linearly over `episodes` episodes, then stays at `end`
+    Linear {
+        start: f64,
+        end: f64,
+        episodes: usize,
+    },
+    /// $\max(\text{start} \cdot \text{rate}^t, \text{min})$ on the $t$-th episode
+    Exponential {
+        start: f64,
+        rate: f64,
+        min: f64,
+    },
+    /// $1 / n$ on the $n$-th visit of a state-action pair
+    ///
+    /// Schedules consulted per episode have no visit count and stay at $1$.
+    InverseVisits,
+    /// $1 / k$ on the $k$-th episode, e.g. a GLIE $\epsilon$
+    InverseEpisodes,
+}
+
+impl Schedule {
+    /// - `t`: number of episodes finished before the current one
+    /// - `n`: number of visits of the current state-action pair, including the current one
+    pub fn value(&self, t: usize, n: usize) -> f64 {
+        match *self {
+            Schedule::Constant(x) => x,
+            Schedule::Linear {
+                start,
+                end,
+                episodes,
+            } => {
+                if episodes <= t {
+                    return end;
+                }
+                start + (end - start) * t as f64 / episodes as f64
+            }
+            Schedule::Exponential { start, rate, min } => {
+                f64::max(start * rate.powi(t as i32), min)
+            }
+            Schedule::InverseVisits => 1.0 / n.max(1) as f64,
+            Schedule::InverseEpisodes => 1.0 / (t + 1) as f64,
+        }
+    }
+}
+
+impl From<f64> for Schedule {
+    fn from(x: f64) -> Self {
+        Schedule::Constant(x)
+    }
+}
@@ -0,0 +1,515 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{history::History, StateActionPair};
+
+/// On-disk format of tables
+///
+/// Every format stores a table as a list of rows:
+///
+/// - action values: `(state, action, value)`
+/// - state values: `(state, value)`
+/// - policies: `(state, action)`, one row per action
+///
+/// CSV has no header and flattens states and actions into their fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    /// Compact, via `bincode`
+    Binary,
+}
+impl Format {
+    /// Guess the format from the extension of `path`: `.json`, `.csv` or `.bin`
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "bin" => Some(Format::Binary),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    Binary(bincode::Error),
+    /// The data cannot be stored in this format
+    UnsupportedFormat(Format),
+    /// The checkpoint was saved by another kind of run than the one resuming it
+    CheckpointMismatch {
+        expected: CheckpointHeader,
+        found: CheckpointHeader,
+    },
+}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "{}", e),
+            Error::Csv(e) => write!(f, "{}", e),
+            Error::Binary(e) => write!(f, "{}", e),
+            Error::UnsupportedFormat(format) => write!(f, "unsupported format: {:?}", format),
+            Error::CheckpointMismatch { expected, found } => write!(
+                f,
+                "the checkpoint is of {}, not of {}; remove it or use another output directory",
+                found, expected
+            ),
+        }
+    }
+}
+impl std::error::Error for Error {}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Csv(e)
+    }
+}
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Self {
+        Error::Binary(e)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ActionValue<State, Action> {
+    state: State,
+    action: Action,
+    value: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateValue<State> {
+    state: State,
+    value: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PolicyAction<State, Action> {
+    state: State,
+    action: Action,
+}
+
+/// Save $Q$
+pub fn save_action_values<State, Action>(
+    path: impl AsRef<Path>,
+    format: Format,
+    value: &HashMap<StateActionPair<State, Action>, f64>,
+) -> Result<(), Error>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + Serialize,
+    Action: Copy + std::hash::Hash + std::cmp::Eq + Serialize,
+{
+    let rows: Vec<ActionValue<State, Action>> = value
+        .iter()
+        .map(|(sa, v)| ActionValue {
+            state: sa.state,
+            action: sa.action,
+            value: *v,
+        })
+        .collect();
+    save_rows(path, format, &rows)
+}
+
+/// Load $Q$
+pub fn load_action_values<State, Action>(
+    path: impl AsRef<Path>,
+    format: Format,
+) -> Result<HashMap<StateActionPair<State, Action>, f64>, Error>
+where
+    State: Copy + std::hash::Hash + std::cmp::Eq + DeserializeOwned,
+    Action: Copy + std::hash::Hash + std::cmp::Eq + DeserializeOwned,
+{
+    let rows: Vec<ActionValue<State, Action>> = load_rows(path, format)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let sa = StateActionPair {
+                state: row.state,
+                action: row.action,
+            };
+            (sa, row.value)
+        })
+        .collect())
+}
+
+/// Save $V$
+pub fn save_state_values<State>(
+    path: impl AsRef<Path>,
+    format: Format,
+    value: &HashMap<State, f64>,
+) -> Result<(), Error>
+where
+    State: Copy + Serialize,
+{
+    let rows: Vec<StateValue<State>> = value
+        .iter()
+        .map(|(s, v)| StateValue {
+            state: *s,
+            value: *v,
+        })
+        .collect();
+    save_rows(path, format, &rows)
+}
+
+/// Load $V$
+pub fn load_state_values<State>(
+    path: impl AsRef<Path>,
+    format: Format,
+) -> Result<HashMap<State, f64>, Error>
+where
+    State: std::hash::Hash + std::cmp::Eq + DeserializeOwned,
+{
+    let rows: Vec<StateValue<State>> = load_rows(path, format)?;
+    Ok(rows.into_iter().map(|row| (row.state, row.value)).collect())
+}
+
+/// Save $\pi$ as the set of actions of each state
+pub fn save_policy<State, Action>(
+    path: impl AsRef<Path>,
+    format: Format,
+    policy: &HashMap<State, Vec<Action>>,
+) -> Result<(), Error>
+where
+    State: Copy + Serialize,
+    Action: Copy + Serialize,
+{
+    let rows: Vec<PolicyAction<State, Action>> = policy
+        .iter()
+        .flat_map(|(s, a)| {
+            a.iter().map(|a| PolicyAction {
+                state: *s,
+                action: *a,
+            })
+        })
+        .collect();
+    save_rows(path, format, &rows)
+}
+
+/// Load $\pi$ as the set of actions of each state
+pub fn load_policy<State, Action>(
+    path: impl AsRef<Path>,
+    format: Format,
+) -> Result<HashMap<State, Vec<Action>>, Error>
+where
+    State: std::hash::Hash + std::cmp::Eq + DeserializeOwned,
+    Action: DeserializeOwned,
+{
+    let rows: Vec<PolicyAction<State, Action>> = load_rows(path, format)?;
+    let mut policy: HashMap<State, Vec<Action>> = HashMap::new();
+    for row in rows {
+        policy.entry(row.state).or_default().push(row.action);
+    }
+    Ok(policy)
+}
+
+/// Save the per-episode statistics of a training run, one row per episode
+pub fn save_history(
+    path: impl AsRef<Path>,
+    format: Format,
+    history: &History,
+) -> Result<(), Error> {
+    save_rows(path, format, &history.episodes)
+}
+
+/// Load the per-episode statistics of a training run
+pub fn load_history(path: impl AsRef<Path>, format: Format) -> Result<History, Error> {
+    Ok(History {
+        episodes: load_rows(path, format)?,
+    })
+}
+
+fn save_rows<Row>(path: impl AsRef<Path>, format: Format, rows: &[Row]) -> Result<(), Error>
+where
+    Row: Serialize,
+{
+    let file = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Json => serde_json::to_writer_pretty(file, rows)?,
+        Format::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(file);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        Format::Binary => bincode::serialize_into(file, rows)?,
+    }
+    Ok(())
+}
+
+fn load_rows<Row>(path: impl AsRef<Path>, format: Format) -> Result<Vec<Row>, Error>
+where
+    Row: DeserializeOwned,
+{
+    let file = BufReader::new(File::open(path)?);
+    let rows = match format {
+        Format::Json => serde_json::from_reader(file)?,
+        Format::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(file);
+            reader.deserialize().collect::<Result<_, _>>()?
+        }
+        Format::Binary => bincode::deserialize_from(file)?,
+    };
+    Ok(rows)
+}
+
+/// Version of the checkpoint layout, bumped whenever learner states change shape
+pub const CHECKPOINT_VERSION: u32 = 1;
+/// Headers are tiny; a bigger one is not a header
+const CHECKPOINT_HEADER_LIMIT: u64 = 1 << 16;
+
+/// What a checkpoint was trained on, saved ahead of the learner state
+///
+/// A checkpoint only resumes a run with an equal header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointHeader {
+    pub version: u32,
+    /// The task and the learner, e.g. `Blackjack QLearning`
+    pub task: String,
+    /// The hyperparameters the learner state depends on, as JSON
+    pub params: String,
+}
+impl CheckpointHeader {
+    pub fn new(task: impl Into<String>, params: &impl Serialize) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            task: task.into(),
+            params: serde_json::to_string(params).unwrap(),
+        }
+    }
+}
+impl std::fmt::Display for CheckpointHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} with {} (format {})",
+            self.task, self.params, self.version
+        )
+    }
+}
+
+/// Save a learner state in JSON or binary, after `header`
+pub fn save_checkpoint<T>(
+    path: impl AsRef<Path>,
+    format: Format,
+    header: &CheckpointHeader,
+    state: &T,
+) -> Result<(), Error>
+where
+    T: Serialize,
+{
+    let mut file = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Json => {
+            serde_json::to_writer(&mut file, header)?;
+            serde_json::to_writer(&mut file, state)?;
+        }
+        Format::Binary => {
+            bincode::serialize_into(&mut file, header)?;
+            bincode::serialize_into(&mut file, state)?;
+        }
+        Format::Csv => return Err(Error::UnsupportedFormat(format)),
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Load a learner state saved by [`save_checkpoint`], if its header is `header`
+pub fn load_checkpoint<T>(
+    path: impl AsRef<Path>,
+    format: Format,
+    header: &CheckpointHeader,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut file = BufReader::new(File::open(path)?);
+    let mismatch = |found: Option<CheckpointHeader>| Error::CheckpointMismatch {
+        expected: header.clone(),
+        found: found.unwrap_or_else(|| CheckpointHeader {
+            version: 0,
+            task: "an unknown run".to_string(),
+            params: "no header".to_string(),
+        }),
+    };
+    let state = match format {
+        Format::Json => {
+            let mut deserializer = serde_json::Deserializer::from_reader(file);
+            let found = CheckpointHeader::deserialize(&mut deserializer).ok();
+            if found.as_ref() != Some(header) {
+                return Err(mismatch(found));
+            }
+            T::deserialize(&mut deserializer)?
+        }
+        Format::Binary => {
+            let found: Option<CheckpointHeader> = bincode::options()
+                .with_fixint_encoding()
+                .allow_trailing_bytes()
+                .with_limit(CHECKPOINT_HEADER_LIMIT)
+                .deserialize_from(&mut file)
+                .ok();
+            if found.as_ref() != Some(header) {
+                return Err(mismatch(found));
+            }
+            bincode::deserialize_from(file)?
+        }
+        Format::Csv => return Err(Error::UnsupportedFormat(format)),
+    };
+    Ok(state)
+}
+
+/// Serde adapter for maps with keys that JSON cannot take, as a list of `(key, value)` pairs
+pub(crate) mod map_as_pairs {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + std::hash::Hash + std::cmp::Eq,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs: Vec<(K, V)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::games::blackjack::{Action, State};
+
+    const FORMATS: [(Format, &str); 3] = [
+        (Format::Json, "json"),
+        (Format::Csv, "csv"),
+        (Format::Binary, "bin"),
+    ];
+
+    fn temp_path(name: &str, extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "serialization-{}-{}.{}",
+            std::process::id(),
+            name,
+            extension
+        ))
+    }
+
+    #[test]
+    fn state_values_round_trip() {
+        // values whose shortest decimal forms need all 17 digits
+        let value: HashMap<(u32, u32), f64> = HashMap::from([
+            ((0, 1), 0.1 + 0.2),
+            ((20, 20), -1.0 / 3.0),
+            ((7, 3), 1e-300),
+            ((3, 7), f64::MAX),
+        ]);
+        for (format, extension) in FORMATS {
+            let path = temp_path("value", extension);
+            save_state_values(&path, format, &value).unwrap();
+            let loaded: HashMap<(u32, u32), f64> = load_state_values(&path, format).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded, value, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn policy_round_trip() {
+        let state = |dealer, me, useful_ace| State {
+            dealer,
+            me,
+            useful_ace,
+            after_stick: false,
+        };
+        let policy: HashMap<State, Vec<Action>> = HashMap::from([
+            (state(10, 17, true), vec![Action::Hit]),
+            (state(11, 20, false), vec![Action::Stick]),
+            (state(2, 12, false), vec![Action::Hit, Action::Stick]),
+        ]);
+        for (format, extension) in FORMATS {
+            let path = temp_path("policy", extension);
+            save_policy(&path, format, &policy).unwrap();
+            let loaded: HashMap<State, Vec<Action>> = load_policy(&path, format).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded, policy, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn csv_rows_are_flat() {
+        let path = temp_path("flat", "csv");
+        let policy = HashMap::from([(
+            State {
+                dealer: 10,
+                me: 17,
+                useful_ace: true,
+                after_stick: false,
+            },
+            vec![Action::Hit],
+        )]);
+        save_policy(&path, Format::Csv, &policy).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text, "10,17,true,false,Hit\n");
+    }
+
+    #[test]
+    fn malformed_rows_are_errors() {
+        let rows = [
+            (
+                Format::Json,
+                "json",
+                "[{\"state\": [0, 1], \"value\": \"high\"}]",
+            ),
+            (Format::Csv, "csv", "0,1,0.5\n0,2,high\n"),
+            (Format::Binary, "bin", "\u{1}"),
+        ];
+        for (format, extension, text) in rows {
+            let path = temp_path("malformed", extension);
+            std::fs::write(&path, text).unwrap();
+            let loaded: Result<HashMap<(u32, u32), f64>, Error> = load_state_values(&path, format);
+            std::fs::remove_file(&path).unwrap();
+            match (format, loaded) {
+                (Format::Json, Err(Error::Json(_)))
+                | (Format::Csv, Err(Error::Csv(_)))
+                | (Format::Binary, Err(Error::Binary(_))) => (),
+                (format, loaded) => panic!("{:?}: {:?}", format, loaded),
+            }
+        }
+    }
+}
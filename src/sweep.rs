@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{evaluation::EvaluationReport, history::History, serialization::Error};
+
+/// Hyperparameter values of one configuration, by name
+pub type Params = BTreeMap<String, f64>;
+
+/// How configurations are picked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Search {
+    /// Every combination of the listed values
+    Grid(BTreeMap<String, Vec<f64>>),
+    /// `samples` configurations, each value drawn independently from its interval
+    Random {
+        ranges: BTreeMap<String, Interval>,
+        samples: usize,
+        seed: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+    /// Draw uniformly in $\ln x$ rather than in $x$, for step sizes and the like
+    #[serde(default)]
+    pub log: bool,
+}
+
+impl Interval {
+    /// Finite with `low <= high`, and positive if `log`, so that values can be drawn
+    pub fn check(&self) -> Result<(), String> {
+        if !self.low.is_finite() || !self.high.is_finite() || self.low > self.high {
+            return Err(format!("invalid interval [{}, {}]", self.low, self.high));
+        }
+        if self.log && self.low <= 0.0 {
+            return Err(format!(
+                "log interval [{}, {}] must be positive",
+                self.low, self.high
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Search {
+    /// Every configuration to run; an error if an interval has nothing to draw from
+    pub fn configurations(&self) -> Result<Vec<Params>, String> {
+        match self {
+            Search::Grid(grid) => {
+                let mut configurations = vec![Params::new()];
+                for (name, values) in grid {
+                    configurations = configurations
+                        .into_iter()
+                        .flat_map(|params| {
+                            values.iter().map(move |v| {
+                                let mut params = params.clone();
+                                params.insert(name.clone(), *v);
+                                params
+                            })
+                        })
+                        .collect();
+                }
+                Ok(configurations)
+            }
+            Search::Random {
+                ranges,
+                samples,
+                seed,
+            } => {
+                for (name, interval) in ranges {
+                    interval.check().map_err(|e| format!("{}: {}", name, e))?;
+                }
+                let mut rng = ChaCha8Rng::seed_from_u64(*seed);
+                Ok((0..*samples)
+                    .map(|_| {
+                        ranges
+                            .iter()
+                            .map(|(name, interval)| {
+                                let v = if interval.log {
+                                    f64::exp(rng.gen_range(interval.low.ln()..=interval.high.ln()))
+                                } else {
+                                    rng.gen_range(interval.low..=interval.high)
+                                };
+                                (name.clone(), v)
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Aggregate over the seeds of one configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Summary {
+    pub params: Params,
+    pub seeds: Vec<u64>,
+    /// Mean over seeds of the mean return of the final evaluation
+    pub evaluation_mean: f64,
+    /// Standard error over seeds of the mean return of the final evaluation
+    pub evaluation_std_error: f64,
+    /// Mean training return over all episodes and seeds, i.e. the normalized area under the learning curve
+    pub training_mean: f64,
+    /// Mean training return over the last tenth of the episodes
+    pub training_final: f64,
+    /// Mean over seeds of the return of each training episode, cut to the shortest run
+    pub learning_curve: Vec<f64>,
+}
+
+impl Summary {
+    fn new(params: Params, seeds: Vec<u64>, runs: Vec<(History, EvaluationReport)>) -> Self {
+        let n = runs.len() as f64;
+        let evaluation_mean = runs.iter().map(|(_, e)| e.mean).sum::<f64>() / n;
+        let evaluation_std_error = if runs.len() < 2 {
+            0.0
+        } else {
+            let variance = runs
+                .iter()
+                .map(|(_, e)| (e.mean - evaluation_mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            f64::sqrt(variance / n)
+        };
+
+        let len = runs
+            .iter()
+            .map(|(h, _)| h.episodes.len())
+            .min()
+            .unwrap_or(0);
+        let mut learning_curve = vec![0.0; len];
+        for (history, _) in &runs {
+            for (mean, episode) in learning_curve.iter_mut().zip(&history.episodes) {
+                *mean += episode.ret / n;
+            }
+        }
+        let mean = |returns: &[f64]| returns.iter().sum::<f64>() / returns.len() as f64;
+        let tail = usize::max(len / 10, 1).min(len);
+        Self {
+            params,
+            seeds,
+            evaluation_mean,
+            evaluation_std_error,
+            training_mean: mean(&learning_curve),
+            training_final: mean(&learning_curve[len - tail..]),
+            learning_curve,
+        }
+    }
+}
+
+/// Run every configuration once per seed, on up to `threads` threads
+///
+/// `run` trains a learner from scratch with the given hyperparameters and seed,
+/// and evaluates the result.
+pub fn sweep<F>(configurations: &[Params], seeds: &[u64], threads: usize, run: F) -> Vec<Summary>
+where
+    F: Fn(&Params, u64) -> (History, EvaluationReport) + Sync,
+{
+    let jobs: Vec<(usize, u64)> = (0..configurations.len())
+        .flat_map(|c| seeds.iter().map(move |seed| (c, *seed)))
+        .collect();
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; jobs.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..usize::max(threads, 1) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((c, seed)) = jobs.get(i) else {
+                    break;
+                };
+                let result = run(&configurations[*c], *seed);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap);
+    configurations
+        .iter()
+        .map(|params| {
+            let runs = results.by_ref().take(seeds.len()).collect();
+            Summary::new(params.clone(), seeds.to_vec(), runs)
+        })
+        .collect()
+}
+
+/// Write one CSV row per configuration, best evaluation first
+pub fn save_summary_table(path: impl AsRef<Path>, summaries: &[Summary]) -> Result<(), Error> {
+    let mut summaries: Vec<&Summary> = summaries.iter().collect();
+    summaries.sort_by(|a, b| b.evaluation_mean.total_cmp(&a.evaluation_mean));
+    let names: Vec<String> = summaries
+        .first()
+        .map(|s| s.params.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = names.clone();
+    header.extend(
+        [
+            "seeds",
+            "evaluation_mean",
+            "evaluation_std_error",
+            "training_mean",
+            "training_final",
+        ]
+        .map(String::from),
+    );
+    writer.write_record(&header)?;
+    for summary in summaries {
+        let mut record: Vec<String> = names
+            .iter()
+            .map(|name| summary.params[name].to_string())
+            .collect();
+        record.push(summary.seeds.len().to_string());
+        record.push(summary.evaluation_mean.to_string());
+        record.push(summary.evaluation_std_error.to_string());
+        record.push(summary.training_mean.to_string());
+        record.push(summary.training_final.to_string());
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use rand::{seq::SliceRandom, RngCore};
+
+/// How close two values must be to count as a tie
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// $|a - b| \leq t$
+    Absolute(f64),
+    /// $|a - b| \leq t \max(|a|, |b|)$
+    Relative(f64),
+}
+impl Tolerance {
+    pub fn ties(&self, a: f64, b: f64) -> bool {
+        let diff = f64::abs(a - b);
+        match *self {
+            Tolerance::Absolute(t) => diff <= t,
+            Tolerance::Relative(t) => diff <= t * f64::max(a.abs(), b.abs()),
+        }
+    }
+}
+
+/// Which of the tied actions to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreaking {
+    All,
+    Smallest,
+    /// One drawn uniformly by [`Ties::break_ties`]; [`Ties::max`] keeps them all
+    Random,
+    /// The first one in action space order
+    First,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ties {
+    pub tolerance: Tolerance,
+    pub breaking: TieBreaking,
+}
+impl Default for Ties {
+    /// Exact equality, keeping all tied actions
+    fn default() -> Self {
+        Self {
+            tolerance: Tolerance::Absolute(0.0),
+            breaking: TieBreaking::All,
+        }
+    }
+}
+impl Ties {
+    /// Return the max value and the actions whose values tie with it
+    ///
+    /// Deterministic: under `TieBreaking::Random` all tied actions are returned, so that a
+    /// uniform choice among them can be made with the caller's RNG by `break_ties`.
+    pub fn max<Action>(&self, values: impl Iterator<Item = (Action, f64)>) -> (f64, Vec<Action>)
+    where
+        Action: Copy + std::cmp::Ord,
+    {
+        let values: Vec<(Action, f64)> = values.collect();
+        let max_v = values.iter().map(|(_, v)| *v).fold(f64::MIN, f64::max);
+        let max_a: Vec<Action> = values
+            .iter()
+            .filter(|(_, v)| self.tolerance.ties(*v, max_v))
+            .map(|(a, _)| *a)
+            .collect();
+        let max_a = match self.breaking {
+            TieBreaking::All | TieBreaking::Random => max_a,
+            TieBreaking::Smallest => max_a.iter().min().copied().into_iter().collect(),
+            TieBreaking::First => max_a.first().copied().into_iter().collect(),
+        };
+        (max_v, max_a)
+    }
+
+    /// Keep one of the tied actions from `max`, drawn with `rng`, under `TieBreaking::Random`
+    pub fn break_ties<Action>(&self, actions: Vec<Action>, rng: &mut dyn RngCore) -> Vec<Action>
+    where
+        Action: Copy,
+    {
+        match self.breaking {
+            TieBreaking::Random => actions.choose(rng).copied().into_iter().collect(),
+            TieBreaking::All | TieBreaking::Smallest | TieBreaking::First => actions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn ties(tolerance: Tolerance, breaking: TieBreaking) -> Ties {
+        Ties {
+            tolerance,
+            breaking,
+        }
+    }
+
+    #[test]
+    fn absolute_tolerance() {
+        let values = [(0, 1.0), (1, 1.0 - 0.0009), (2, 1.0 - 0.0011), (3, 0.5)];
+        let all = ties(Tolerance::Absolute(0.001), TieBreaking::All);
+        assert_eq!(all.max(values.into_iter()), (1.0, vec![0, 1]));
+        assert_eq!(Ties::default().max(values.into_iter()), (1.0, vec![0]));
+    }
+
+    #[test]
+    fn relative_tolerance() {
+        let values = [(0, -100.0), (1, -100.09), (2, -100.11)];
+        let all = ties(Tolerance::Relative(0.001), TieBreaking::All);
+        assert_eq!(all.max(values.into_iter()), (-100.0, vec![0, 1]));
+    }
+
+    #[test]
+    fn breaking() {
+        let values = [(3, 2.0), (1, 2.0), (2, 1.0)];
+        let tolerance = Tolerance::Absolute(0.0);
+        let max = |breaking| ties(tolerance, breaking).max(values.into_iter()).1;
+        assert_eq!(max(TieBreaking::All), vec![3, 1]);
+        assert_eq!(max(TieBreaking::Smallest), vec![1]);
+        assert_eq!(max(TieBreaking::First), vec![3]);
+        assert_eq!(max(TieBreaking::Random), vec![3, 1]);
+    }
+
+    #[test]
+    fn random_breaking_follows_the_rng() {
+        let random = ties(Tolerance::Absolute(0.0), TieBreaking::Random);
+        let pick = |seed| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            random.break_ties((0..10).collect(), &mut rng)
+        };
+        assert_eq!(pick(0).len(), 1);
+        assert_eq!(pick(0), pick(0));
+        assert_eq!(
+            ties(Tolerance::Absolute(0.0), TieBreaking::All)
+                .break_ties(vec![1, 2], &mut ChaCha8Rng::seed_from_u64(0)),
+            vec![1, 2]
+        );
+    }
+}
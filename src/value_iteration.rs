@@ -1,4 +1,13 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{policy::Policy, ties::Ties};
 
 pub trait ValueIterationTask<State, Action>
 where
@@ -12,59 +21,252 @@ where
     fn terminal_state_space(&self) -> Box<dyn Iterator<Item = State>>;
 }
 
+/// The order in which states are backed up during a sweep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateScheme {
+    /// Jacobi-style: every backup of a sweep reads the values of the previous sweep
+    Synchronous,
+    /// Gauss-Seidel: backups in `state_space()` order, immediately visible to later backups
+    InPlace,
+    /// In place, in a freshly shuffled order on every sweep, drawn from the seeded RNG
+    RandomAsync,
+    /// In place, in descending order of the Bellman residual seen on the previous sweep
+    ///
+    /// Still full sweeps, only reordered by residuals that may be stale by a whole sweep; not
+    /// prioritized sweeping, which backs up one state at a time off a priority queue.
+    Prioritized,
+}
+
+/// When to stop sweeping
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// The max residual $\Delta$ of a sweep falls below $\theta$
+    Residual(f64),
+    /// The greedy actions from `max_v_a` have been unchanged for this many consecutive sweeps
+    PolicyStable(usize),
+    /// The span semi-norm $\max_s (v_{k+1} - v_k) - \min_s (v_{k+1} - v_k)$ falls below this tolerance
+    ///
+    /// Useful for undiscounted and average-reward problems, where values can drift by a constant
+    Span(f64),
+}
+
 pub struct ValueIteration<State, Action>
 where
     State: std::hash::Hash + std::cmp::Eq,
     Action: Copy,
 {
     task: Box<dyn ValueIterationTask<State, Action>>,
+    ties: Ties,
+    seed: u64,
 }
 
 impl<State, Action> ValueIteration<State, Action>
 where
-    State: std::hash::Hash + std::cmp::Eq,
-    Action: Copy,
+    State: Copy + std::hash::Hash + std::cmp::Eq,
+    Action: Copy + std::cmp::Ord,
 {
     pub fn new(task: Box<dyn ValueIterationTask<State, Action>>) -> Self {
-        Self { task }
+        Self {
+            task,
+            ties: Ties::default(),
+            seed: 0,
+        }
+    }
+
+    pub fn with_ties(mut self, ties: Ties) -> Self {
+        self.ties = ties;
+        self
+    }
+
+    /// Seed of the RNG that `UpdateScheme::RandomAsync` shuffles with, on every `value_iteration`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
     }
     // type Value = HashMap<State, f64>;
-    pub fn value_iteration(&self, theta: f64, v: &mut HashMap<State, f64>) {
+    /// - `max_sweeps`: give up after this many sweeps even if `termination` is not met
+    pub fn value_iteration(
+        &self,
+        termination: Termination,
+        scheme: UpdateScheme,
+        max_sweeps: Option<usize>,
+        v: &mut HashMap<State, f64>,
+    ) -> ValueIterationReport {
+        let start = Instant::now();
+        let mut residuals_per_sweep = vec![];
+
+        for s in self.task.terminal_state_space() {
+            v.insert(s, 0.0);
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut states: Vec<State> = self.task.state_space().collect();
+        // residuals of the last backup of each state, used as priorities
+        let mut residuals: HashMap<State, f64> = HashMap::new();
+        let mut last_policy = None;
+        let mut stable_sweeps = 0;
+
+        let mut converged = false;
+        while !converged {
+            if max_sweeps.is_some_and(|max| residuals_per_sweep.len() >= max) {
+                break;
+            }
+            match scheme {
+                UpdateScheme::RandomAsync => states.shuffle(&mut rng),
+                UpdateScheme::Prioritized => states.sort_by(|a, b| {
+                    let a = residuals.get(a).unwrap_or(&f64::MAX);
+                    let b = residuals.get(b).unwrap_or(&f64::MAX);
+                    b.total_cmp(a)
+                }),
+                UpdateScheme::Synchronous | UpdateScheme::InPlace => (),
+            }
+
+            let old = v.clone();
+            let mut delta: f64 = 0.0;
+            let mut max_diff = f64::MIN;
+            let mut min_diff = f64::MAX;
+            let mut policy = HashMap::new();
+            for s in &states {
+                let (new_v, a) = match scheme {
+                    UpdateScheme::Synchronous => self.max_v_a(&old, s),
+                    _ => self.max_v_a(v, s),
+                };
+                v.insert(*s, new_v);
+                let diff = new_v - old[s];
+                residuals.insert(*s, diff.abs());
+                delta = delta.max(diff.abs());
+                max_diff = max_diff.max(diff);
+                min_diff = min_diff.min(diff);
+                policy.insert(*s, a);
+            }
+            residuals_per_sweep.push(delta);
+
+            converged = match termination {
+                Termination::Residual(theta) => delta < theta,
+                Termination::Span(tolerance) => max_diff - min_diff < tolerance,
+                Termination::PolicyStable(k) => {
+                    if last_policy.as_ref() == Some(&policy) {
+                        stable_sweeps += 1;
+                    } else {
+                        stable_sweeps = 0;
+                    }
+                    last_policy = Some(policy);
+                    stable_sweeps >= k
+                }
+            };
+        }
+
+        let delta = *residuals_per_sweep.last().unwrap_or(&f64::INFINITY);
+        ValueIterationReport {
+            converged,
+            suboptimality_bound: suboptimality_bound(delta, self.task.gamma()),
+            sweeps: residuals_per_sweep.len(),
+            residuals: residuals_per_sweep,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Iterative policy evaluation of $v_\pi$, in place
+    ///
+    /// - `max_sweeps`: give up after this many sweeps even if $\Delta$ is still above `theta`
+    ///
+    /// Return whether $\Delta$ fell below `theta`.
+    pub fn evaluate_policy(
+        &self,
+        policy: &dyn Policy<State, Action>,
+        theta: f64,
+        max_sweeps: Option<usize>,
+        v: &mut HashMap<State, f64>,
+    ) -> bool
+    where
+        Action: 'static,
+    {
         for s in self.task.terminal_state_space() {
             v.insert(s, 0.0);
         }
 
         let mut delta = f64::MAX;
+        let mut sweeps = 0;
         while delta >= theta {
+            if max_sweeps.is_some_and(|max| sweeps >= max) {
+                return false;
+            }
+            sweeps += 1;
             delta = 0.0;
             for s in self.task.state_space() {
                 let old_v = v[&s];
-                let (new_v, _) = self.max_v_a(v, &s);
+                let mut new_v = 0.0;
+                for (a, prob_a) in policy.distribution(&s, self.task.action_space(&s)) {
+                    new_v += prob_a * self.q(v, &s, &a);
+                }
                 v.insert(s, new_v);
                 delta = f64::max(delta, f64::abs(new_v - old_v));
             }
         }
+        true
     }
 
-    pub fn max_v_a(&self, v: &HashMap<State, f64>, s: &State) -> (f64, Vec<Action>) {
-        let mut max_v = f64::MIN;
-        let mut max_a = vec![];
-        for a in self.task.action_space(s) {
-            let mut expected_v = 0.0;
-            let probabilities = self.task.possibilities(s, &a);
-            for probability in probabilities {
-                expected_v += probability.probability
-                    * (probability.reward + self.task.gamma() * v[&probability.next_state]);
-            }
-            if max_v < expected_v {
-                max_a = vec![a];
+    /// Alternate policy evaluation and greedy improvement from `policy` until the policy is stable
+    ///
+    /// The policy is stable once all its actions are still greedy, within the tolerance of the
+    /// ties, so that policies switching between equally good actions (Exercise 4.4) stop too.
+    /// - `max_sweeps`: passed on to every `evaluate_policy`
+    /// - `max_iterations`: give up after this many evaluations even if the policy is not stable
+    ///
+    /// Return every policy on the way, starting with `policy`; `v` ends as $v_\pi$ of the last one.
+    pub fn policy_iteration(
+        &self,
+        mut policy: HashMap<State, Vec<Action>>,
+        theta: f64,
+        max_sweeps: Option<usize>,
+        max_iterations: Option<usize>,
+        v: &mut HashMap<State, f64>,
+    ) -> Vec<HashMap<State, Vec<Action>>>
+    where
+        Action: 'static,
+    {
+        let mut policies = vec![];
+        loop {
+            self.evaluate_policy(&policy, theta, max_sweeps, v);
+            let mut stable = true;
+            let mut improved = HashMap::new();
+            for s in self.task.state_space() {
+                let q: Vec<(Action, f64)> = self
+                    .task
+                    .action_space(&s)
+                    .map(|a| (a, self.q(v, &s, &a)))
+                    .collect();
+                let (max_v, a) = self.ties.max(q.iter().copied());
+                stable &= policy.get(&s).is_some_and(|old| {
+                    old.iter().all(|old_a| {
+                        q.iter()
+                            .any(|(a, q_a)| a == old_a && self.ties.tolerance.ties(*q_a, max_v))
+                    })
+                });
+                improved.insert(s, a);
             }
-            if max_v == expected_v {
-                max_a.push(a);
+            policies.push(policy);
+            if stable || max_iterations.is_some_and(|max| policies.len() >= max) {
+                return policies;
             }
-            max_v = f64::max(max_v, expected_v);
+            policy = improved;
         }
-        (max_v, max_a)
+    }
+
+    pub fn max_v_a(&self, v: &HashMap<State, f64>, s: &State) -> (f64, Vec<Action>) {
+        self.ties
+            .max(self.task.action_space(s).map(|a| (a, self.q(v, s, &a))))
+    }
+
+    /// $\sum_{s', r} p(s', r \mid s, a) [r + \gamma v(s')]$
+    fn q(&self, v: &HashMap<State, f64>, s: &State, a: &Action) -> f64 {
+        self.task
+            .possibilities(s, a)
+            .iter()
+            .map(|p| p.probability * (p.reward + self.task.gamma() * v[&p.next_state]))
+            .sum()
     }
 
     pub fn task(&self) -> &dyn ValueIterationTask<State, Action> {
@@ -72,6 +274,28 @@ where
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueIterationReport {
+    /// Max Bellman residual $\Delta$ of each sweep
+    pub residuals: Vec<f64>,
+    pub sweeps: usize,
+    pub elapsed: Duration,
+    /// Whether `termination` was met rather than hitting `max_sweeps`
+    pub converged: bool,
+    /// Upper bound on $\|v_{\pi} - v_*\|_\infty$ for the greedy policy $\pi$ w.r.t. the returned values
+    ///
+    /// `None` if $\gamma \geq 1$, where the bound does not hold
+    pub suboptimality_bound: Option<f64>,
+}
+
+/// $2 \Delta \gamma / (1 - \gamma)$ for the max residual $\Delta$ of the last sweep
+fn suboptimality_bound(delta: f64, gamma: f64) -> Option<f64> {
+    if gamma >= 1.0 {
+        return None;
+    }
+    Some(2.0 * delta * gamma / (1.0 - gamma))
+}
+
 pub struct Possibility<State> {
     pub probability: f64,
     pub next_state: State,